@@ -0,0 +1,458 @@
+/*
+    Command Line Interface
+        runs the classifier and its companions on a graph read from a file or stdin
+
+    Usage
+        dfs_edge_classification [<command> [<file> | -] [--format text|json]]
+
+    With no arguments the built-in 8-vertex example is classified, as before.
+*/
+
+use std::{fmt::Write, fs, io::Read};
+
+use dfs_edge_classification::{
+    CycleError, EdgeClass, Graph, dfs,
+    edge_list::{LoadedGraph, parse_edge_list},
+    scc::strongly_connected_components,
+};
+
+const USAGE: &str = "\
+usage: dfs_edge_classification [<command> [<file> | -] [--format text|json]]
+
+commands:
+    classify   label every edge as tree, back, forward or cross
+    toposort   print a topological order, or a cycle if there is none
+    scc        print the strongly connected components
+    cycles     print the cycle closed by every back edge
+
+Input is an edge list with one `u v` pair (or a lone vertex) per line and `#`
+comments. Vertices are numbers from 0 to 1048575, or names as soon as any token
+is not a number.
+The graph is read from stdin when <file> is missing or `-`.
+With no arguments, classifies the built-in 8-vertex example.
+";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Command {
+    Classify,
+    Toposort,
+    Scc,
+    Cycles,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Options {
+    command: Command,
+    input: Option<String>,
+    format: Format,
+}
+
+/// run()
+///
+/// Parses `args` (without the program name), loads the graph and prints the
+/// report for the requested command.
+///
+/// Returns the process exit code: 0 on success, 1 when `toposort` finds a
+/// cycle or the input cannot be read, 2 on a usage error.
+///
+pub fn run(args: &[String]) -> i32 {
+    if args.is_empty() {
+        print!("{}", classify(&example(), Format::Text));
+        return 0;
+    }
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        print!("{}", USAGE);
+        return 0;
+    }
+
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprint!("error: {}\n\n{}", message, USAGE);
+            return 2;
+        }
+    };
+
+    let text = match read_input(options.input.as_deref()) {
+        Ok(text) => text,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            return 1;
+        }
+    };
+    let loaded = match parse_edge_list(&text) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return 1;
+        }
+    };
+
+    let (report, ok) = execute(options.command, &loaded, options.format);
+    print!("{}", report);
+    if ok { 0 } else { 1 }
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut positional = Vec::new();
+    let mut format = Format::Text;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = if arg == "--format" {
+            Some(iter.next().ok_or("--format needs a value")?.as_str())
+        } else {
+            arg.strip_prefix("--format=")
+        };
+        match value {
+            Some("text") => format = Format::Text,
+            Some("json") => format = Format::Json,
+            Some(other) => return Err(format!("unknown format `{}`", other)),
+            None if arg.starts_with("--") => return Err(format!("unknown option `{}`", arg)),
+            None => positional.push(arg.as_str()),
+        }
+    }
+
+    let command = match positional.first().copied() {
+        Some("classify") => Command::Classify,
+        Some("toposort") => Command::Toposort,
+        Some("scc") => Command::Scc,
+        Some("cycles") => Command::Cycles,
+        Some(other) => return Err(format!("unknown command `{}`", other)),
+        None => return Err("missing command".to_string()),
+    };
+    if positional.len() > 2 {
+        return Err(format!("unexpected argument `{}`", positional[2]));
+    }
+    let input = positional
+        .get(1)
+        .filter(|&&p| p != "-")
+        .map(|p| p.to_string());
+
+    Ok(Options {
+        command,
+        input,
+        format,
+    })
+}
+
+fn read_input(path: Option<&str>) -> Result<String, String> {
+    match path {
+        Some(path) => fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e)),
+        None => {
+            let mut text = String::new();
+            std::io::stdin()
+                .read_to_string(&mut text)
+                .map_err(|e| format!("stdin: {}", e))?;
+            Ok(text)
+        }
+    }
+}
+
+// Returns the report and whether the command succeeded.
+fn execute(command: Command, loaded: &LoadedGraph, format: Format) -> (String, bool) {
+    match command {
+        Command::Classify => (classify(loaded, format), true),
+        Command::Toposort => toposort(loaded, format),
+        Command::Scc => (scc(loaded, format), true),
+        Command::Cycles => (cycles(loaded, format), true),
+    }
+}
+
+fn example() -> LoadedGraph {
+    // 0:A, 1:B, 2:C, 3:D, 4:E, 5:F, 6:G, 7:H
+    let mut g = Graph::new(8);
+    g.add_edge(0, 1); // A→B
+    g.add_edge(1, 2); // B→C
+    g.add_edge(2, 0); // C→A (creates a cycle among A-B-C)
+    g.add_edge(2, 3); // C→D
+    g.add_edge(3, 4); // D→E
+    g.add_edge(4, 5); // E→F
+    g.add_edge(5, 3); // F→D (another cycle)
+    g.add_edge(6, 7); // G→H (disconnected component)
+    g.add_edge(0, 4); // A→E (forward or cross depending on traversal)
+    LoadedGraph {
+        graph: g,
+        names: (0..8).map(|v| v.to_string()).collect(),
+        named: false,
+    }
+}
+
+fn class_name(class: EdgeClass) -> &'static str {
+    match class {
+        EdgeClass::Tree => "tree",
+        EdgeClass::Back => "back",
+        EdgeClass::Forward => "forward",
+        EdgeClass::Cross => "cross",
+    }
+}
+
+// Numeric vertices print as `v3` in per-vertex lines, named ones as their name.
+fn vertex_label(loaded: &LoadedGraph, v: usize) -> String {
+    if loaded.named {
+        loaded.names[v].clone()
+    } else {
+        format!("v{}", v)
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_names(loaded: &LoadedGraph, vertices: &[usize]) -> String {
+    let items: Vec<String> = vertices
+        .iter()
+        .map(|&v| json_string(&loaded.names[v]))
+        .collect();
+    format!("[{}]", items.join(", "))
+}
+
+fn text_names(loaded: &LoadedGraph, vertices: &[usize]) -> String {
+    let items: Vec<&str> = vertices.iter().map(|&v| loaded.names[v].as_str()).collect();
+    items.join(" ")
+}
+
+fn classify(loaded: &LoadedGraph, format: Format) -> String {
+    let result = dfs(&loaded.graph);
+    let names = &loaded.names;
+    let mut out = String::new();
+
+    match format {
+        Format::Text => {
+            let status = if result.is_cyclic {
+                "cyclic"
+            } else {
+                "acyclic"
+            };
+            let _ = writeln!(out, "Graph is {}", status);
+            for e in &result.edges {
+                let class = class_name(e.class);
+                let _ = writeln!(
+                    out,
+                    "{} -> {} : {}",
+                    names[e.source], names[e.target], class
+                );
+            }
+            for u in 0..loaded.graph.node_count {
+                let parent_str = match result.parent[u] {
+                    Some(p) => names[p].clone(),
+                    None => String::from("nil"),
+                };
+                let _ = writeln!(
+                    out,
+                    "{}: d={}, f={}, parent={}",
+                    vertex_label(loaded, u),
+                    result.discovery_time[u],
+                    result.finish_time[u],
+                    parent_str
+                );
+            }
+        }
+        Format::Json => {
+            let edges: Vec<String> = result
+                .edges
+                .iter()
+                .map(|e| {
+                    format!(
+                        "{{\"source\": {}, \"target\": {}, \"class\": \"{}\"}}",
+                        json_string(&names[e.source]),
+                        json_string(&names[e.target]),
+                        class_name(e.class)
+                    )
+                })
+                .collect();
+            let vertices: Vec<String> = (0..loaded.graph.node_count)
+                .map(|u| {
+                    let parent =
+                        result.parent[u].map_or("null".to_string(), |p| json_string(&names[p]));
+                    format!(
+                        "{{\"name\": {}, \"discovery\": {}, \"finish\": {}, \"parent\": {}}}",
+                        json_string(&names[u]),
+                        result.discovery_time[u],
+                        result.finish_time[u],
+                        parent
+                    )
+                })
+                .collect();
+            let _ = writeln!(
+                out,
+                "{{\"cyclic\": {}, \"edges\": [{}], \"vertices\": [{}]}}",
+                result.is_cyclic,
+                edges.join(", "),
+                vertices.join(", ")
+            );
+        }
+    }
+    out
+}
+
+fn toposort(loaded: &LoadedGraph, format: Format) -> (String, bool) {
+    let result = dfs(&loaded.graph).topological_order();
+    let mut out = String::new();
+    match (format, &result) {
+        (Format::Text, Ok(order)) => {
+            let _ = writeln!(out, "{}", text_names(loaded, order));
+        }
+        (Format::Text, Err(CycleError { cycle })) => {
+            let _ = writeln!(out, "cycle: {}", text_names(loaded, cycle));
+        }
+        (Format::Json, Ok(order)) => {
+            let order = json_names(loaded, order);
+            let _ = writeln!(out, "{{\"acyclic\": true, \"order\": {}}}", order);
+        }
+        (Format::Json, Err(CycleError { cycle })) => {
+            let cycle = json_names(loaded, cycle);
+            let _ = writeln!(out, "{{\"acyclic\": false, \"cycle\": {}}}", cycle);
+        }
+    }
+    (out, result.is_ok())
+}
+
+fn scc(loaded: &LoadedGraph, format: Format) -> String {
+    let members = strongly_connected_components(&loaded.graph).members();
+    let mut out = String::new();
+    match format {
+        Format::Text => {
+            for component in &members {
+                let _ = writeln!(out, "{}", text_names(loaded, component));
+            }
+        }
+        Format::Json => {
+            let components: Vec<String> = members.iter().map(|c| json_names(loaded, c)).collect();
+            let _ = writeln!(out, "{{\"components\": [{}]}}", components.join(", "));
+        }
+    }
+    out
+}
+
+fn cycles(loaded: &LoadedGraph, format: Format) -> String {
+    let cycles = dfs(&loaded.graph).back_edge_cycles();
+    let mut out = String::new();
+    match format {
+        Format::Text => {
+            for CycleError { cycle } in &cycles {
+                let _ = writeln!(out, "{}", text_names(loaded, cycle));
+            }
+        }
+        Format::Json => {
+            let items: Vec<String> = cycles
+                .iter()
+                .map(|c| json_names(loaded, &c.cycle))
+                .collect();
+            let _ = writeln!(out, "{{\"cycles\": [{}]}}", items.join(", "));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(&args(&["scc", "deps.txt", "--format", "json"])),
+            Ok(Options {
+                command: Command::Scc,
+                input: Some("deps.txt".into()),
+                format: Format::Json,
+            })
+        );
+        assert_eq!(
+            parse_args(&args(&["--format=text", "cycles", "-"])),
+            Ok(Options {
+                command: Command::Cycles,
+                input: None,
+                format: Format::Text
+            })
+        );
+        assert!(parse_args(&args(&["sort"])).is_err());
+        assert!(parse_args(&args(&["scc", "--format", "xml"])).is_err());
+        assert!(parse_args(&args(&["scc", "a", "b"])).is_err());
+        assert!(parse_args(&args(&["--format", "json"])).is_err());
+    }
+
+    #[test]
+    fn test_classify_example_matches_original_output() {
+        let report = classify(&example(), Format::Text);
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "Graph is cyclic");
+        assert_eq!(lines[1], "0 -> 1 : tree");
+        assert!(lines.contains(&"2 -> 0 : back"));
+        assert!(lines.contains(&"0 -> 4 : forward"));
+        assert_eq!(lines.last(), Some(&"v7: d=14, f=15, parent=6"));
+    }
+
+    #[test]
+    fn test_named_commands() {
+        let loaded = parse_edge_list("app core\ncore util\napp \"ui\"\n").unwrap();
+        let (report, ok) = execute(Command::Toposort, &loaded, Format::Text);
+        assert!(ok);
+        assert_eq!(report, "app \"ui\" core util\n");
+
+        let (report, _) = execute(Command::Toposort, &loaded, Format::Json);
+        assert_eq!(
+            report,
+            "{\"acyclic\": true, \"order\": [\"app\", \"\\\"ui\\\"\", \"core\", \"util\"]}\n"
+        );
+
+        let loaded = parse_edge_list("a b\nb a\nb c\n").unwrap();
+        let (report, ok) = execute(Command::Toposort, &loaded, Format::Text);
+        assert!(!ok);
+        assert_eq!(report, "cycle: a b\n");
+        let (report, _) = execute(Command::Scc, &loaded, Format::Text);
+        assert_eq!(report, "a b\nc\n");
+        let (report, _) = execute(Command::Cycles, &loaded, Format::Json);
+        assert_eq!(report, "{\"cycles\": [[\"a\", \"b\"]]}\n");
+
+        let (report, _) = execute(Command::Classify, &loaded, Format::Json);
+        assert!(report.starts_with("{\"cyclic\": true, \"edges\": [{\"source\": \"a\""));
+        assert!(
+            report
+                .contains("{\"name\": \"c\", \"discovery\": 3, \"finish\": 4, \"parent\": \"b\"}")
+        );
+    }
+
+    #[test]
+    fn test_long_chain_through_run() {
+        // A 200,000-vertex path would overflow the stack of a recursive search
+        let n = 200_000;
+        let mut text: String = (1..n).map(|v| format!("{} {}\n", v - 1, v)).collect();
+        let path = std::env::temp_dir().join(format!("dfs_chain_{}.txt", std::process::id()));
+        fs::write(&path, &text).unwrap();
+        let file = path.to_str().unwrap();
+        assert_eq!(run(&args(&["toposort", file])), 0);
+        assert_eq!(run(&args(&["scc", file, "--format", "json"])), 0);
+
+        // Closing the chain into one cycle: toposort reports it and fails
+        text.push_str(&format!("{} 0\n", n - 1));
+        fs::write(&path, &text).unwrap();
+        assert_eq!(run(&args(&["toposort", file])), 1);
+        assert_eq!(run(&args(&["scc", file])), 0);
+        assert_eq!(run(&args(&["cycles", file])), 0);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,239 @@
+/*
+    Critical Path Analysis
+        longest paths through a DAG of timed tasks
+
+    Model
+        every vertex v is a task with duration dur(v)
+        every edge u → v may carry a lag: v cannot start until lag(u, v) after u finishes
+
+    Passes (over the topological order from `dfs`)
+        forward    ES(v) = max over u → v of ES(u) + dur(u) + lag(u, v), 0 for sources
+        backward   LS(u) = min over u → v of LS(v) - lag(u, v) - dur(u), makespan - dur(u) for sinks
+        slack(v)   LS(v) - ES(v); tasks with zero slack are critical
+*/
+
+use crate::{CycleError, Graph, dfs};
+
+/// ScheduleError
+///
+/// An Error Type for all errors a critical path analysis can produce.
+///
+#[derive(Debug, PartialEq, Eq)]
+pub enum ScheduleError {
+    Cycle(CycleError),
+    DurationCountMismatch {
+        vertices: usize,
+        durations: usize,
+    },
+    LagCountMismatch {
+        vertex: usize,
+        edges: usize,
+        lags: usize,
+    },
+}
+
+/// Schedule
+///
+/// Earliest and latest start times of every task, the total length of the
+/// schedule, and one chain of critical tasks from a source to a task that
+/// finishes at `makespan`.
+///
+#[derive(Debug, PartialEq, Eq)]
+pub struct Schedule {
+    pub earliest_start: Vec<u64>,
+    pub latest_start: Vec<u64>,
+    pub slack: Vec<u64>,
+    pub makespan: u64,
+    pub critical_path: Vec<usize>,
+}
+
+impl Schedule {
+    /// is_critical()
+    ///
+    /// Returns true if delaying task `v` would delay the whole schedule.
+    ///
+    pub fn is_critical(&self, v: usize) -> bool {
+        self.slack[v] == 0
+    }
+}
+
+/// critical_path()
+///
+/// Runs the analysis with weighted vertices only (every lag is 0).
+///
+/// Returns the Schedule, or an error if the graph has a cycle.
+///
+pub fn critical_path(graph: &Graph, durations: &[u64]) -> Result<Schedule, ScheduleError> {
+    let lags: Vec<Vec<u64>> = graph
+        .adj_list
+        .iter()
+        .map(|adj| vec![0; adj.len()])
+        .collect();
+    critical_path_with_lags(graph, durations, &lags)
+}
+
+/// critical_path_with_lags()
+///
+/// Runs the analysis with weighted vertices and weighted edges. `lags[u][i]` is
+/// the weight of the edge `u → graph.adj_list[u][i]`.
+///
+/// Returns the Schedule, or an error if the inputs do not match the graph or
+/// `dfs` finds a back edge.
+///
+pub fn critical_path_with_lags(
+    graph: &Graph,
+    durations: &[u64],
+    lags: &[Vec<u64>],
+) -> Result<Schedule, ScheduleError> {
+    let n = graph.node_count;
+    if durations.len() != n {
+        return Err(ScheduleError::DurationCountMismatch {
+            vertices: n,
+            durations: durations.len(),
+        });
+    }
+    for (vertex, adj) in graph.adj_list.iter().enumerate() {
+        let given = lags.get(vertex).map_or(0, |l| l.len());
+        if given != adj.len() {
+            return Err(ScheduleError::LagCountMismatch {
+                vertex,
+                edges: adj.len(),
+                lags: given,
+            });
+        }
+    }
+
+    let order = dfs(graph)
+        .topological_order()
+        .map_err(ScheduleError::Cycle)?;
+
+    let mut earliest_start = vec![0u64; n];
+    for &u in &order {
+        let finish = earliest_start[u] + durations[u];
+        for (&v, &lag) in graph.adj_list[u].iter().zip(&lags[u]) {
+            earliest_start[v] = earliest_start[v].max(finish + lag);
+        }
+    }
+    let makespan = (0..n)
+        .map(|v| earliest_start[v] + durations[v])
+        .max()
+        .unwrap_or(0);
+
+    let mut latest_start = vec![0u64; n];
+    for &u in order.iter().rev() {
+        let latest_finish = graph.adj_list[u]
+            .iter()
+            .zip(&lags[u])
+            .map(|(&v, &lag)| latest_start[v] - lag)
+            .min()
+            .unwrap_or(makespan);
+        latest_start[u] = latest_finish - durations[u];
+    }
+
+    let slack: Vec<u64> = (0..n)
+        .map(|v| latest_start[v] - earliest_start[v])
+        .collect();
+
+    // Follow tight edges between zero-slack tasks until one finishes at the makespan.
+    let mut critical_path = Vec::new();
+    let mut cur = order
+        .iter()
+        .copied()
+        .find(|&v| slack[v] == 0 && earliest_start[v] == 0);
+    while let Some(u) = cur {
+        critical_path.push(u);
+        let finish = earliest_start[u] + durations[u];
+        if finish == makespan {
+            break;
+        }
+        cur = graph.adj_list[u]
+            .iter()
+            .zip(&lags[u])
+            .find(|&(&v, &lag)| slack[v] == 0 && earliest_start[v] == finish + lag)
+            .map(|(&v, _)| v);
+    }
+
+    Ok(Schedule {
+        earliest_start,
+        latest_start,
+        slack,
+        makespan,
+        critical_path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0: fetch(2) → 1: compile(5) → 3: link(1) → 4: package(2)
+    //      └──────→ 2: docs(3) ───────────────────────┘
+    fn pipeline() -> Graph {
+        let mut g = Graph::new(5);
+        for (u, v) in [(0, 1), (0, 2), (1, 3), (3, 4), (2, 4)] {
+            g.add_edge(u, v);
+        }
+        g
+    }
+
+    #[test]
+    fn test_weighted_vertices() {
+        let s = critical_path(&pipeline(), &[2, 5, 3, 1, 2]).unwrap();
+        assert_eq!(s.makespan, 10);
+        assert_eq!(s.earliest_start, vec![0, 2, 2, 7, 8]);
+        assert_eq!(s.latest_start, vec![0, 2, 5, 7, 8]);
+        assert_eq!(s.slack, vec![0, 0, 3, 0, 0]);
+        assert_eq!(s.critical_path, vec![0, 1, 3, 4]);
+        assert!(!s.is_critical(2));
+    }
+
+    #[test]
+    fn test_weighted_edges() {
+        // A 4 unit lag after docs moves it onto the critical path
+        let lags = vec![vec![0, 0], vec![0], vec![4], vec![0], vec![]];
+        let s = critical_path_with_lags(&pipeline(), &[2, 5, 3, 1, 2], &lags).unwrap();
+        assert_eq!(s.makespan, 11);
+        assert_eq!(s.earliest_start, vec![0, 2, 2, 7, 9]);
+        assert_eq!(s.slack, vec![0, 1, 0, 1, 0]);
+        assert_eq!(s.critical_path, vec![0, 2, 4]);
+    }
+
+    #[test]
+    fn test_independent_tasks_and_empty() {
+        let s = critical_path(&Graph::new(3), &[4, 9, 1]).unwrap();
+        assert_eq!(s.makespan, 9);
+        assert_eq!(s.slack, vec![5, 0, 8]);
+        assert_eq!(s.critical_path, vec![1]);
+
+        let s = critical_path(&Graph::new(0), &[]).unwrap();
+        assert_eq!(s.makespan, 0);
+        assert!(s.critical_path.is_empty());
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut g = pipeline();
+        g.add_edge(4, 1);
+        assert_eq!(
+            critical_path(&g, &[1; 5]).unwrap_err(),
+            ScheduleError::Cycle(CycleError {
+                cycle: vec![1, 3, 4]
+            })
+        );
+        assert_eq!(
+            critical_path(&pipeline(), &[1; 4]).unwrap_err(),
+            ScheduleError::DurationCountMismatch {
+                vertices: 5,
+                durations: 4
+            }
+        );
+        assert_eq!(
+            critical_path_with_lags(&pipeline(), &[1; 5], &[vec![0, 0], vec![]]).unwrap_err(),
+            ScheduleError::LagCountMismatch {
+                vertex: 1,
+                edges: 1,
+                lags: 0
+            }
+        );
+    }
+}
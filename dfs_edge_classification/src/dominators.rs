@@ -0,0 +1,472 @@
+/*
+    Dominators
+        computed from an entry vertex with the Lengauer–Tarjan algorithm
+
+    Definitions
+        d dominates v   every path from the entry to v passes through d
+        idom(v)         the strict dominator of v that all other strict dominators of v dominate
+        DF(d)           vertices v where d dominates a predecessor of v but not strictly v
+        natural loop    a back edge (latch → header) whose header dominates the latch
+
+    Note
+        The semidominator pass walks vertices in the preorder given by the DFS discovery times
+        of `dfs_from_roots`, so only vertices reachable from the entry take part.
+*/
+
+use crate::{EdgeClass, Graph, dfs_from_roots};
+
+/// NaturalLoop
+///
+/// A loop found from a back edge `latch → header` where `header` dominates `latch`.
+/// `body` holds every vertex that reaches `latch` without passing through `header`,
+/// including the header and latch themselves, in ascending order.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NaturalLoop {
+    pub header: usize,
+    pub latch: usize,
+    pub body: Vec<usize>,
+}
+
+/// Dominators
+///
+/// The dominator information of a flow graph rooted at `entry`.
+/// Vertices unreachable from the entry have no immediate dominator, no tree children
+/// and an empty frontier.
+///
+#[derive(Debug)]
+pub struct Dominators {
+    pub entry: usize,
+    pub idom: Vec<Option<usize>>,
+    pub tree: Vec<Vec<usize>>,
+    pub frontier: Vec<Vec<usize>>,
+    pub loops: Vec<NaturalLoop>,
+    // Pre/post numbering of the dominator tree, used for O(1) dominance checks.
+    tree_in: Vec<usize>,
+    tree_out: Vec<usize>,
+}
+
+impl Dominators {
+    /// is_reachable()
+    ///
+    /// Returns true if `v` can be reached from the entry.
+    ///
+    pub fn is_reachable(&self, v: usize) -> bool {
+        v == self.entry || self.idom[v].is_some()
+    }
+
+    /// dominates()
+    ///
+    /// Every reachable vertex dominates itself.
+    ///
+    /// Returns true if `a` dominates `b`, in O(1).
+    ///
+    pub fn dominates(&self, a: usize, b: usize) -> bool {
+        self.is_reachable(a)
+            && self.is_reachable(b)
+            && self.tree_in[a] <= self.tree_in[b]
+            && self.tree_out[b] <= self.tree_out[a]
+    }
+
+    /// strictly_dominates()
+    ///
+    /// Returns true if `a` dominates `b` and `a != b`.
+    ///
+    pub fn strictly_dominates(&self, a: usize, b: usize) -> bool {
+        a != b && self.dominates(a, b)
+    }
+}
+
+/// dominators()
+///
+/// Computes immediate dominators with the Lengauer–Tarjan algorithm (path compression
+/// without balancing, O(E log V)), then derives the dominator tree, the dominance
+/// frontiers and the natural loops.
+///
+/// Returns the Dominators of `graph` from `entry`.
+///
+pub fn dominators(graph: &Graph, entry: usize) -> Dominators {
+    assert!(entry < graph.node_count, "vertex out of range");
+    let n = graph.node_count;
+    let result = dfs_from_roots(graph, [entry]);
+
+    // Preorder numbering from the DFS discovery times.
+    let mut order: Vec<usize> = (0..n).filter(|&v| result.discovery_time[v] != 0).collect();
+    order.sort_unstable_by_key(|&v| result.discovery_time[v]);
+    let mut num: Vec<Option<usize>> = vec![None; n];
+    for (i, &v) in order.iter().enumerate() {
+        num[v] = Some(i);
+    }
+
+    let mut preds: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for (u, adj) in graph.adj_list.iter().enumerate() {
+        if num[u].is_some() {
+            for &v in adj {
+                preds[v].push(u);
+            }
+        }
+    }
+
+    // All of the following are indexed by preorder number.
+    let count = order.len();
+    let parent: Vec<usize> = order
+        .iter()
+        .map(|&v| result.parent[v].map_or(0, |p| num[p].unwrap()))
+        .collect();
+    let mut semi: Vec<usize> = (0..count).collect();
+    let mut ancestor: Vec<Option<usize>> = vec![None; count];
+    let mut best: Vec<usize> = (0..count).collect();
+    let mut idom: Vec<usize> = vec![0; count];
+    let mut samedom: Vec<Option<usize>> = vec![None; count];
+    let mut bucket: Vec<Vec<usize>> = vec![Vec::new(); count];
+
+    // Walk up the link forest from `v`, compressing the path as we go.
+    // Returns the vertex with the smallest semidominator on that path.
+    fn eval(v: usize, ancestor: &mut [Option<usize>], best: &mut [usize], semi: &[usize]) -> usize {
+        let mut path = Vec::new();
+        let mut cur = v;
+        while let Some(a) = ancestor[cur] {
+            if ancestor[a].is_none() {
+                break;
+            }
+            path.push(cur);
+            cur = a;
+        }
+        // `cur` is now the last vertex below the forest root; fold back down.
+        for &w in path.iter().rev() {
+            let a = ancestor[w].unwrap();
+            if semi[best[a]] < semi[best[w]] {
+                best[w] = best[a];
+            }
+            ancestor[w] = ancestor[a];
+        }
+        best[v]
+    }
+
+    for w in (1..count).rev() {
+        let p = parent[w];
+        let mut s = p;
+        for &pred in &preds[order[w]] {
+            let v = num[pred].unwrap();
+            let candidate = if v <= w {
+                v
+            } else {
+                semi[eval(v, &mut ancestor, &mut best, &semi)]
+            };
+            if candidate < s {
+                s = candidate;
+            }
+        }
+        semi[w] = s;
+        bucket[s].push(w);
+        ancestor[w] = Some(p);
+
+        for v in std::mem::take(&mut bucket[p]) {
+            let y = eval(v, &mut ancestor, &mut best, &semi);
+            if semi[y] == semi[v] {
+                idom[v] = p;
+            } else {
+                samedom[v] = Some(y);
+            }
+        }
+    }
+    for w in 1..count {
+        if let Some(y) = samedom[w] {
+            idom[w] = idom[y];
+        }
+    }
+
+    let mut idom_vertex: Vec<Option<usize>> = vec![None; n];
+    let mut tree: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for w in 1..count {
+        let (v, d) = (order[w], order[idom[w]]);
+        idom_vertex[v] = Some(d);
+        tree[d].push(v);
+    }
+
+    // Number the dominator tree so dominance becomes interval nesting.
+    let mut tree_in = vec![0; n];
+    let mut tree_out = vec![0; n];
+    let mut clock = 0;
+    let mut stack = vec![(entry, 0)];
+    while let Some((v, next_child)) = stack.pop() {
+        if next_child == 0 {
+            clock += 1;
+            tree_in[v] = clock;
+        }
+        if let Some(&c) = tree[v].get(next_child) {
+            stack.push((v, next_child + 1));
+            stack.push((c, 0));
+        } else {
+            clock += 1;
+            tree_out[v] = clock;
+        }
+    }
+
+    // Dominance frontiers (Cooper, Harvey & Kennedy): walk up from each predecessor
+    // of a vertex until reaching the vertex's immediate dominator. Vertices with a
+    // single predecessor are not skipped: the entry has no immediate dominator, so
+    // one back edge into it already puts it in the frontiers along the walk.
+    let mut frontier: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for &b in &order {
+        for &p in &preds[b] {
+            let mut runner = Some(p);
+            while let Some(r) = runner {
+                if Some(r) == idom_vertex[b] {
+                    break;
+                }
+                if !frontier[r].contains(&b) {
+                    frontier[r].push(b);
+                }
+                runner = idom_vertex[r];
+            }
+        }
+    }
+    for df in &mut frontier {
+        df.sort_unstable();
+    }
+
+    let mut doms = Dominators {
+        entry,
+        idom: idom_vertex,
+        tree,
+        frontier,
+        loops: Vec::new(),
+        tree_in,
+        tree_out,
+    };
+
+    // Back edges of the DFS from the entry whose target dominates their source.
+    for e in result.edges.iter().filter(|e| e.class == EdgeClass::Back) {
+        let (latch, header) = (e.source, e.target);
+        if !doms.dominates(header, latch) {
+            continue;
+        }
+        let mut in_body = vec![false; n];
+        in_body[header] = true;
+        let mut stack = Vec::new();
+        if !in_body[latch] {
+            in_body[latch] = true;
+            stack.push(latch);
+        }
+        while let Some(v) = stack.pop() {
+            for &p in &preds[v] {
+                if !in_body[p] {
+                    in_body[p] = true;
+                    stack.push(p);
+                }
+            }
+        }
+        let body = (0..n).filter(|&v| in_body[v]).collect();
+        doms.loops.push(NaturalLoop {
+            header,
+            latch,
+            body,
+        });
+    }
+
+    doms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::xorshift;
+
+    // Naive iterative dataflow: dom(v) = {v} ∪ ⋂ dom(p) over predecessors p.
+    fn naive_dominator_sets(graph: &Graph, entry: usize) -> Vec<Option<Vec<bool>>> {
+        let n = graph.node_count;
+        let reachable = dfs_from_roots(graph, [entry]).discovery_time;
+        let mut dom: Vec<Option<Vec<bool>>> = (0..n)
+            .map(|v| {
+                if reachable[v] == 0 {
+                    None
+                } else if v == entry {
+                    Some((0..n).map(|u| u == entry).collect())
+                } else {
+                    Some(vec![true; n])
+                }
+            })
+            .collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for v in 0..n {
+                if v == entry || dom[v].is_none() {
+                    continue;
+                }
+                let mut next = vec![true; n];
+                for (u, du) in dom.iter().enumerate() {
+                    if let Some(du) = du
+                        && graph.adj_list[u].contains(&v)
+                    {
+                        for (bit, &d) in next.iter_mut().zip(du) {
+                            *bit &= d;
+                        }
+                    }
+                }
+                next[v] = true;
+                if dom[v].as_ref() != Some(&next) {
+                    dom[v] = Some(next);
+                    changed = true;
+                }
+            }
+        }
+        dom
+    }
+
+    // DF(x) = {y : x dominates a predecessor of y and does not strictly dominate y}.
+    fn naive_frontier(graph: &Graph, doms: &Dominators, x: usize) -> Vec<usize> {
+        (0..graph.node_count)
+            .filter(|&y| {
+                (0..graph.node_count)
+                    .any(|p| graph.adj_list[p].contains(&y) && doms.dominates(x, p))
+                    && !doms.strictly_dominates(x, y)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_lengauer_tarjan_paper_example() {
+        // R A B C D E F G H I J K L
+        // 0 1 2 3 4 5 6 7 8 9 10 11 12
+        let mut g = Graph::new(13);
+        for (u, v) in [
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (1, 4),
+            (2, 1),
+            (2, 4),
+            (2, 5),
+            (3, 6),
+            (3, 7),
+            (4, 12),
+            (5, 8),
+            (6, 9),
+            (7, 9),
+            (7, 10),
+            (8, 5),
+            (8, 11),
+            (9, 11),
+            (10, 9),
+            (11, 9),
+            (11, 0),
+            (12, 8),
+        ] {
+            g.add_edge(u, v);
+        }
+        let doms = dominators(&g, 0);
+        let expected = [
+            None,
+            Some(0),
+            Some(0),
+            Some(0),
+            Some(0),
+            Some(0),
+            Some(3),
+            Some(3),
+            Some(0),
+            Some(0),
+            Some(7),
+            Some(0),
+            Some(4),
+        ];
+        assert_eq!(doms.idom, expected);
+
+        // Tree children agree with idom
+        assert_eq!(doms.tree[3], vec![6, 7]);
+        assert_eq!(doms.tree[7], vec![10]);
+        assert!(doms.dominates(3, 10));
+        assert!(!doms.dominates(2, 4));
+        assert!(doms.dominates(0, 0) && !doms.strictly_dominates(0, 0));
+    }
+
+    #[test]
+    fn test_matches_naive_on_random_graphs() {
+        let mut next = xorshift(0x2545_f491_4f6c_dd1d);
+        for _ in 0..200 {
+            let n = (next() % 12 + 1) as usize;
+            let m = (next() % 30) as usize;
+            let mut g = Graph::new(n);
+            for _ in 0..m {
+                g.add_edge((next() % n as u64) as usize, (next() % n as u64) as usize);
+            }
+            let doms = dominators(&g, 0);
+            let naive = naive_dominator_sets(&g, 0);
+            for a in 0..n {
+                for (b, dom_b) in naive.iter().enumerate() {
+                    let expected = dom_b.as_ref().is_some_and(|d| d[a]);
+                    assert_eq!(doms.dominates(a, b), expected, "dominates({a}, {b})");
+                }
+                assert_eq!(doms.frontier[a], naive_frontier(&g, &doms, a), "DF({a})");
+            }
+        }
+    }
+
+    #[test]
+    fn test_dominance_frontier() {
+        // 0 -> 1 -> 2 -> 4, 1 -> 3 -> 4, 4 -> 1 (loop), 4 -> 5
+        let mut g = Graph::new(7);
+        for (u, v) in [(0, 1), (1, 2), (1, 3), (2, 4), (3, 4), (4, 1), (4, 5)] {
+            g.add_edge(u, v);
+        }
+        let doms = dominators(&g, 0);
+        assert_eq!(
+            doms.idom,
+            vec![None, Some(0), Some(1), Some(1), Some(1), Some(4), None]
+        );
+        assert_eq!(doms.frontier[2], vec![4]);
+        assert_eq!(doms.frontier[3], vec![4]);
+        assert_eq!(doms.frontier[4], vec![1]);
+        assert_eq!(doms.frontier[1], vec![1]);
+        assert!(doms.frontier[0].is_empty());
+
+        // Vertex 6 is unreachable
+        assert!(!doms.is_reachable(6));
+        assert!(!doms.dominates(0, 6));
+
+        // A single back edge into the entry puts it in the frontiers
+        let mut g = Graph::new(2);
+        g.add_edge(0, 1);
+        g.add_edge(1, 0);
+        assert_eq!(dominators(&g, 0).frontier, vec![vec![0], vec![0]]);
+    }
+
+    #[test]
+    fn test_natural_loops() {
+        let mut g = Graph::new(7);
+        for (u, v) in [(0, 1), (1, 2), (1, 3), (2, 4), (3, 4), (4, 1), (4, 5)] {
+            g.add_edge(u, v);
+        }
+        let doms = dominators(&g, 0);
+        assert_eq!(
+            doms.loops,
+            vec![NaturalLoop {
+                header: 1,
+                latch: 4,
+                body: vec![1, 2, 3, 4]
+            }]
+        );
+
+        // An irreducible cycle has back edges, but none whose target dominates its source
+        let mut g = Graph::new(3);
+        for (u, v) in [(0, 1), (0, 2), (1, 2), (2, 1)] {
+            g.add_edge(u, v);
+        }
+        assert!(dominators(&g, 0).loops.is_empty());
+
+        // A self loop is a natural loop with a single-vertex body
+        let mut g = Graph::new(2);
+        g.add_edge(0, 1);
+        g.add_edge(1, 1);
+        assert_eq!(
+            dominators(&g, 0).loops,
+            vec![NaturalLoop {
+                header: 1,
+                latch: 1,
+                body: vec![1]
+            }]
+        );
+    }
+}
@@ -0,0 +1,198 @@
+/*
+    Edge List Loader
+        reads a directed graph from plain text
+
+    Format
+        one edge `u v` per line, or a single `u` to declare an isolated vertex
+        `#` starts a comment that runs to the end of the line; blank lines are skipped
+
+    Vertices
+        if every token is a number, vertices are 0..=max and keep their numbers;
+        numbers above MAX_VERTEX_NUMBER are rejected rather than allocated for
+        otherwise every token is a name and vertices are numbered by first appearance
+*/
+
+use std::{collections::HashMap, fmt};
+
+use crate::Graph;
+
+/// The largest vertex number accepted in numeric input. Every number up to the
+/// largest one used gets a vertex, so this caps the memory a short file can ask for.
+pub const MAX_VERTEX_NUMBER: usize = (1 << 20) - 1;
+
+/// LoadError
+///
+/// An Error Type for all errors reading an edge list can produce.
+/// Line numbers are 1-based.
+///
+#[derive(Debug, PartialEq, Eq)]
+pub enum LoadError {
+    TooManyTokens { line: usize, tokens: usize },
+    VertexTooLarge { line: usize, vertex: usize },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::TooManyTokens { line, tokens } => {
+                write!(
+                    f,
+                    "line {}: expected `u v` or `u`, found {} tokens",
+                    line, tokens
+                )
+            }
+            LoadError::VertexTooLarge { line, vertex } => write!(
+                f,
+                "line {}: vertex {} is above the largest vertex number {}",
+                line, vertex, MAX_VERTEX_NUMBER
+            ),
+        }
+    }
+}
+
+/// LoadedGraph
+///
+/// A graph read from an edge list, with the name of every vertex.
+/// For numeric input `names[v]` is just `v` written out and `named` is false.
+///
+#[derive(Debug)]
+pub struct LoadedGraph {
+    pub graph: Graph,
+    pub names: Vec<String>,
+    pub named: bool,
+}
+
+/// parse_edge_list()
+///
+/// Returns the LoadedGraph described by `input`, or the first malformed line.
+///
+pub fn parse_edge_list(input: &str) -> Result<LoadedGraph, LoadError> {
+    let mut lines: Vec<Vec<&str>> = Vec::new();
+    let mut line_numbers: Vec<usize> = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let content = line.split('#').next().unwrap_or("");
+        let tokens: Vec<&str> = content.split_whitespace().collect();
+        if tokens.len() > 2 {
+            return Err(LoadError::TooManyTokens {
+                line: i + 1,
+                tokens: tokens.len(),
+            });
+        }
+        if !tokens.is_empty() {
+            lines.push(tokens);
+            line_numbers.push(i + 1);
+        }
+    }
+
+    let numeric = lines.iter().flatten().all(|t| t.parse::<usize>().is_ok());
+    if numeric {
+        let mut n = 0;
+        for (tokens, &line) in lines.iter().zip(&line_numbers) {
+            for token in tokens {
+                let vertex: usize = token.parse().unwrap();
+                if vertex > MAX_VERTEX_NUMBER {
+                    return Err(LoadError::VertexTooLarge { line, vertex });
+                }
+                n = n.max(vertex + 1);
+            }
+        }
+        let mut graph = Graph::new(n);
+        for tokens in &lines {
+            if let [u, v] = tokens.as_slice() {
+                graph.add_edge(u.parse().unwrap(), v.parse().unwrap());
+            }
+        }
+        let names = (0..n).map(|v| v.to_string()).collect();
+        return Ok(LoadedGraph {
+            graph,
+            names,
+            named: false,
+        });
+    }
+
+    let mut names: Vec<String> = Vec::new();
+    let mut lookup: HashMap<&str, usize> = HashMap::new();
+    let mut ids: Vec<Vec<usize>> = Vec::with_capacity(lines.len());
+    for tokens in &lines {
+        let line_ids = tokens
+            .iter()
+            .map(|&t| {
+                *lookup.entry(t).or_insert_with(|| {
+                    names.push(t.to_string());
+                    names.len() - 1
+                })
+            })
+            .collect();
+        ids.push(line_ids);
+    }
+    let mut graph = Graph::new(names.len());
+    for line_ids in &ids {
+        if let [u, v] = line_ids.as_slice() {
+            graph.add_edge(*u, *v);
+        }
+    }
+    Ok(LoadedGraph {
+        graph,
+        names,
+        named: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numeric() {
+        let loaded = parse_edge_list("# header\n0 1\n1 2 # trailing\n\n2 0\n5\n").unwrap();
+        assert!(!loaded.named);
+        assert_eq!(loaded.graph.node_count, 6);
+        assert_eq!(
+            loaded.graph.adj_list,
+            vec![vec![1], vec![2], vec![0], vec![], vec![], vec![]]
+        );
+        assert_eq!(loaded.names[5], "5");
+    }
+
+    #[test]
+    fn test_named() {
+        let loaded = parse_edge_list("core util\napp core\napp util\nlonely\n").unwrap();
+        assert!(loaded.named);
+        assert_eq!(loaded.names, vec!["core", "util", "app", "lonely"]);
+        assert_eq!(
+            loaded.graph.adj_list,
+            vec![vec![1], vec![], vec![0, 1], vec![]]
+        );
+
+        // A single non-numeric token switches every vertex to names
+        let loaded = parse_edge_list("1 2\n2 x\n").unwrap();
+        assert_eq!(loaded.names, vec!["1", "2", "x"]);
+    }
+
+    #[test]
+    fn test_errors_and_empty() {
+        assert_eq!(
+            parse_edge_list("0 1\n0 1 2\n").unwrap_err(),
+            LoadError::TooManyTokens { line: 2, tokens: 3 }
+        );
+        assert_eq!(
+            parse_edge_list("0 1\n# sparse\n1 4000000000\n").unwrap_err(),
+            LoadError::VertexTooLarge {
+                line: 3,
+                vertex: 4_000_000_000
+            }
+        );
+        // usize::MAX + 1 would overflow
+        assert_eq!(
+            parse_edge_list("18446744073709551615\n").unwrap_err(),
+            LoadError::VertexTooLarge {
+                line: 1,
+                vertex: 18_446_744_073_709_551_615
+            }
+        );
+        let loaded = parse_edge_list(&format!("{} 0\n", MAX_VERTEX_NUMBER)).unwrap();
+        assert_eq!(loaded.graph.node_count, MAX_VERTEX_NUMBER + 1);
+        let loaded = parse_edge_list("# nothing here\n").unwrap();
+        assert_eq!(loaded.graph.node_count, 0);
+    }
+}
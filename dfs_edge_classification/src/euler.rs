@@ -0,0 +1,387 @@
+/*
+    Eulerian Paths and Circuits
+        constructed with Hierholzer's algorithm
+
+    Conditions (directed)
+        circuit   every vertex has in-degree == out-degree
+        path      as above, except at most one start (out = in + 1) and one end (in = out + 1)
+
+    Conditions (undirected)
+        circuit   every vertex has even degree
+        path      zero or two vertices have odd degree
+
+    Both forms also need every edge in a single connected component (weakly connected
+    for directed graphs). Isolated vertices are ignored.
+
+    Note
+        The undirected functions read the same `Graph`, treating each adjacency entry
+        u → v as one undirected edge {u, v}.
+*/
+
+use std::fmt;
+
+use crate::Graph;
+
+/// EulerError
+///
+/// One failed condition for an Eulerian path or circuit.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EulerError {
+    // The edges span this many (weakly) connected components instead of one.
+    Disconnected { components: usize },
+    // Vertices whose out-degree minus in-degree breaks the directed degree condition.
+    UnbalancedVertices { vertices: Vec<(usize, isize)> },
+    // Vertices of odd degree that break the undirected degree condition.
+    OddDegreeVertices { vertices: Vec<usize> },
+}
+
+impl fmt::Display for EulerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EulerError::Disconnected { components } => {
+                write!(
+                    f,
+                    "edges are split across {} connected components",
+                    components
+                )
+            }
+            EulerError::UnbalancedVertices { vertices } => {
+                write!(f, "unbalanced vertices (out - in):")?;
+                for (v, diff) in vertices {
+                    write!(f, " {}:{:+}", v, diff)?;
+                }
+                Ok(())
+            }
+            EulerError::OddDegreeVertices { vertices } => {
+                write!(f, "{} vertices have odd degree:", vertices.len())?;
+                for v in vertices {
+                    write!(f, " {}", v)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// eulerian_circuit_directed()
+///
+/// Returns a closed walk using every edge exactly once, as a vertex sequence whose
+/// first and last entries match, or every condition that fails.
+///
+pub fn eulerian_circuit_directed(graph: &Graph) -> Result<Vec<usize>, Vec<EulerError>> {
+    directed(graph, false)
+}
+
+/// eulerian_path_directed()
+///
+/// Returns a walk using every edge exactly once (a circuit when one exists),
+/// or every condition that fails.
+///
+pub fn eulerian_path_directed(graph: &Graph) -> Result<Vec<usize>, Vec<EulerError>> {
+    directed(graph, true)
+}
+
+/// eulerian_circuit_undirected()
+///
+/// Returns a closed walk using every undirected edge exactly once, or every
+/// condition that fails.
+///
+pub fn eulerian_circuit_undirected(graph: &Graph) -> Result<Vec<usize>, Vec<EulerError>> {
+    undirected(graph, false)
+}
+
+/// eulerian_path_undirected()
+///
+/// Returns a walk using every undirected edge exactly once (a circuit when one
+/// exists), or every condition that fails.
+///
+pub fn eulerian_path_undirected(graph: &Graph) -> Result<Vec<usize>, Vec<EulerError>> {
+    undirected(graph, true)
+}
+
+/// de_bruijn()
+///
+/// Builds a de Bruijn sequence B(k, n) from an Eulerian circuit of the de Bruijn
+/// graph: vertices are the k^(n-1) words of length n - 1 and each edge appends a symbol.
+///
+/// Returns a cyclic sequence of length k^n over 0..k containing every word of
+/// length n exactly once.
+///
+pub fn de_bruijn(k: usize, n: u32) -> Vec<usize> {
+    assert!(k > 0 && n > 0, "alphabet and word length must be positive");
+    if n == 1 {
+        return (0..k).collect();
+    }
+    let words = k.pow(n - 1);
+    let mut g = Graph::new(words);
+    for v in 0..words {
+        for a in 0..k {
+            g.add_edge(v, (v * k + a) % words);
+        }
+    }
+    let circuit = eulerian_circuit_directed(&g).expect("de Bruijn graphs are Eulerian");
+
+    // The symbol written by an edge is the last digit of its target word.
+    circuit[1..].iter().map(|&v| v % k).collect()
+}
+
+fn directed(graph: &Graph, allow_path: bool) -> Result<Vec<usize>, Vec<EulerError>> {
+    let n = graph.node_count;
+    let mut in_degree = vec![0usize; n];
+    for adj in &graph.adj_list {
+        for &v in adj {
+            in_degree[v] += 1;
+        }
+    }
+
+    let mut errors = Vec::new();
+    let components = edge_components(graph);
+    if components > 1 {
+        errors.push(EulerError::Disconnected { components });
+    }
+
+    let unbalanced: Vec<(usize, isize)> = (0..n)
+        .map(|v| (v, graph.adj_list[v].len() as isize - in_degree[v] as isize))
+        .filter(|&(_, diff)| diff != 0)
+        .collect();
+    let start = unbalanced
+        .iter()
+        .find(|&&(_, diff)| diff == 1)
+        .map(|&(v, _)| v);
+    let path_shape = unbalanced.len() == 2 && start.is_some();
+    if !(unbalanced.is_empty() || allow_path && path_shape) {
+        errors.push(EulerError::UnbalancedVertices {
+            vertices: unbalanced,
+        });
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let Some(first) = start.or_else(|| (0..n).find(|&v| !graph.adj_list[v].is_empty())) else {
+        return Ok(Vec::new());
+    };
+    let mut adj: Vec<Vec<(usize, usize)>> = vec![Vec::new(); n];
+    let mut id = 0;
+    for (u, targets) in graph.adj_list.iter().enumerate() {
+        for &v in targets {
+            adj[u].push((v, id));
+            id += 1;
+        }
+    }
+    Ok(hierholzer(&adj, first, id))
+}
+
+fn undirected(graph: &Graph, allow_path: bool) -> Result<Vec<usize>, Vec<EulerError>> {
+    let n = graph.node_count;
+    let mut adj: Vec<Vec<(usize, usize)>> = vec![Vec::new(); n];
+    let mut id = 0;
+    for (u, targets) in graph.adj_list.iter().enumerate() {
+        for &v in targets {
+            adj[u].push((v, id));
+            adj[v].push((u, id));
+            id += 1;
+        }
+    }
+
+    let mut errors = Vec::new();
+    let components = edge_components(graph);
+    if components > 1 {
+        errors.push(EulerError::Disconnected { components });
+    }
+
+    // A self loop appears twice in its own list, so it contributes 2 to the degree.
+    let odd: Vec<usize> = (0..n).filter(|&v| adj[v].len() % 2 == 1).collect();
+    if !(odd.is_empty() || allow_path && odd.len() == 2) {
+        errors.push(EulerError::OddDegreeVertices {
+            vertices: odd.clone(),
+        });
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let Some(first) = odd
+        .first()
+        .copied()
+        .or_else(|| (0..n).find(|&v| !adj[v].is_empty()))
+    else {
+        return Ok(Vec::new());
+    };
+    Ok(hierholzer(&adj, first, id))
+}
+
+// Counts the weakly connected components that contain at least one edge.
+fn edge_components(graph: &Graph) -> usize {
+    let n = graph.node_count;
+    let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for (u, targets) in graph.adj_list.iter().enumerate() {
+        for &v in targets {
+            neighbors[u].push(v);
+            neighbors[v].push(u);
+        }
+    }
+
+    let mut seen = vec![false; n];
+    let mut components = 0;
+    for s in 0..n {
+        if seen[s] || neighbors[s].is_empty() {
+            continue;
+        }
+        components += 1;
+        seen[s] = true;
+        let mut stack = vec![s];
+        while let Some(u) = stack.pop() {
+            for &v in &neighbors[u] {
+                if !seen[v] {
+                    seen[v] = true;
+                    stack.push(v);
+                }
+            }
+        }
+    }
+    components
+}
+
+// Iterative Hierholzer over (target, edge id) lists. Follows unused edges until
+// stuck, then backs up, emitting vertices in reverse order.
+fn hierholzer(adj: &[Vec<(usize, usize)>], start: usize, edge_count: usize) -> Vec<usize> {
+    let mut used = vec![false; edge_count];
+    let mut next = vec![0usize; adj.len()];
+    let mut stack = vec![start];
+    let mut walk = Vec::with_capacity(edge_count + 1);
+
+    while let Some(&u) = stack.last() {
+        while next[u] < adj[u].len() && used[adj[u][next[u]].1] {
+            next[u] += 1;
+        }
+        if let Some(&(v, id)) = adj[u].get(next[u]) {
+            used[id] = true;
+            stack.push(v);
+        } else {
+            walk.push(u);
+            stack.pop();
+        }
+    }
+
+    walk.reverse();
+    walk
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::graph;
+
+    // Checks that `walk` uses every edge of `g` exactly once.
+    fn uses_every_edge(g: &Graph, walk: &[usize], undirected: bool) -> bool {
+        let key = |u: usize, v: usize| {
+            if undirected {
+                (u.min(v), u.max(v))
+            } else {
+                (u, v)
+            }
+        };
+        let mut expected: Vec<(usize, usize)> = (0..g.node_count)
+            .flat_map(|u| g.adj_list[u].iter().map(move |&v| key(u, v)))
+            .collect();
+        let mut walked: Vec<(usize, usize)> = walk.windows(2).map(|w| key(w[0], w[1])).collect();
+        expected.sort_unstable();
+        walked.sort_unstable();
+        expected == walked
+    }
+
+    #[test]
+    fn test_directed_circuit() {
+        let g = graph(4, &[(0, 1), (1, 2), (2, 0), (0, 3), (3, 0)]);
+        let circuit = eulerian_circuit_directed(&g).unwrap();
+        assert_eq!(circuit.first(), circuit.last());
+        assert!(uses_every_edge(&g, &circuit, false));
+    }
+
+    #[test]
+    fn test_directed_path() {
+        let g = graph(4, &[(0, 1), (1, 2), (2, 0), (0, 3)]);
+        assert_eq!(
+            eulerian_circuit_directed(&g).unwrap_err(),
+            vec![EulerError::UnbalancedVertices {
+                vertices: vec![(0, 1), (3, -1)]
+            }]
+        );
+        let path = eulerian_path_directed(&g).unwrap();
+        assert_eq!(path.first(), Some(&0));
+        assert_eq!(path.last(), Some(&3));
+        assert!(uses_every_edge(&g, &path, false));
+    }
+
+    #[test]
+    fn test_directed_errors() {
+        // Two disjoint cycles plus a vertex with out-degree 2 and in-degree 0
+        let g = graph(7, &[(0, 1), (1, 0), (2, 3), (3, 2), (4, 5), (4, 6)]);
+        let errors = eulerian_path_directed(&g).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                EulerError::Disconnected { components: 3 },
+                EulerError::UnbalancedVertices {
+                    vertices: vec![(4, 2), (5, -1), (6, -1)]
+                },
+            ]
+        );
+        assert_eq!(
+            errors[1].to_string(),
+            "unbalanced vertices (out - in): 4:+2 5:-1 6:-1"
+        );
+    }
+
+    #[test]
+    fn test_undirected() {
+        // Königsberg: four land masses, seven bridges
+        let g = graph(4, &[(0, 1), (0, 1), (0, 2), (0, 2), (0, 3), (1, 3), (2, 3)]);
+        let errors = eulerian_path_undirected(&g).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![EulerError::OddDegreeVertices {
+                vertices: vec![0, 1, 2, 3]
+            }]
+        );
+
+        // House shape: odd degree only at the two base corners
+        let g = graph(5, &[(0, 1), (1, 2), (2, 3), (3, 0), (0, 2), (2, 4), (4, 3)]);
+        assert!(eulerian_circuit_undirected(&g).is_err());
+        let path = eulerian_path_undirected(&g).unwrap();
+        assert_eq!(path.len(), 8);
+        assert!(uses_every_edge(&g, &path, true));
+
+        // A triangle with a self loop is a circuit
+        let g = graph(3, &[(0, 1), (1, 2), (2, 0), (1, 1)]);
+        let circuit = eulerian_circuit_undirected(&g).unwrap();
+        assert_eq!(circuit.first(), circuit.last());
+        assert!(uses_every_edge(&g, &circuit, true));
+    }
+
+    #[test]
+    fn test_no_edges() {
+        assert_eq!(eulerian_circuit_directed(&Graph::new(3)), Ok(Vec::new()));
+        assert_eq!(eulerian_path_undirected(&Graph::new(0)), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_de_bruijn() {
+        for (k, n) in [(2, 1), (2, 3), (3, 2), (4, 3)] {
+            let seq = de_bruijn(k, n);
+            let len = k.pow(n);
+            assert_eq!(seq.len(), len);
+
+            // Every word of length n appears exactly once as a cyclic window
+            let mut seen = vec![false; len];
+            for i in 0..len {
+                let word = (0..n as usize).fold(0, |w, j| w * k + seq[(i + j) % len]);
+                assert!(!seen[word]);
+                seen[word] = true;
+            }
+        }
+    }
+}
@@ -0,0 +1,346 @@
+/*
+    Feedback Arc Set
+        edges whose removal leaves a directed graph acyclic
+
+    Every vertex ordering defines one: the edges pointing backwards in the order.
+    A minimum feedback arc set is therefore an ordering with as few backward edges
+    as possible, which is NP-hard in general.
+
+    Heuristic (Eades–Lin–Smyth)
+        repeatedly peel sinks onto the back of the order and sources onto the front;
+        when neither exists, move the vertex with the largest out-degree − in-degree
+        to the front. The DFS back edges are also a feedback arc set (dropping them
+        leaves the finish-time order valid), so the smaller of the two is kept.
+
+    Exact
+        only edges inside a strongly connected component can lie on a cycle, so each
+        component is solved on its own with a dynamic program over vertex subsets
+*/
+
+use crate::{EdgeClass, Graph, dfs, scc::strongly_connected_components};
+
+/// Largest strongly connected component the exact solver accepts.
+pub const MAX_EXACT_COMPONENT: usize = 20;
+
+/// FeedbackArcSet
+///
+/// The edges to drop (one entry per parallel edge, self loops included) and a
+/// topological order of the graph that remains.
+///
+#[derive(Debug, PartialEq, Eq)]
+pub struct FeedbackArcSet {
+    pub removed: Vec<(usize, usize)>,
+    pub order: Vec<usize>,
+}
+
+/// FeedbackArcSetError
+///
+/// An Error Type for the exact solver.
+///
+#[derive(Debug, PartialEq, Eq)]
+pub enum FeedbackArcSetError {
+    ComponentTooLarge { size: usize, limit: usize },
+}
+
+/// feedback_arc_set()
+///
+/// Runs the Eades–Lin–Smyth heuristic in O(V² + E) and compares it with the back
+/// edges of `dfs`. An acyclic graph returns no edges straight away.
+///
+/// Returns whichever FeedbackArcSet drops fewer edges.
+///
+pub fn feedback_arc_set(graph: &Graph) -> FeedbackArcSet {
+    let result = dfs(graph);
+    let mut by_finish: Vec<usize> = (0..graph.node_count).collect();
+    by_finish.sort_unstable_by_key(|&v| std::cmp::Reverse(result.finish_time[v]));
+    if !result.is_cyclic {
+        return FeedbackArcSet {
+            removed: Vec::new(),
+            order: by_finish,
+        };
+    }
+
+    let from_dfs = FeedbackArcSet {
+        removed: result
+            .edges
+            .iter()
+            .filter(|e| e.class == EdgeClass::Back)
+            .map(|e| (e.source, e.target))
+            .collect(),
+        order: by_finish,
+    };
+    let from_els = from_order(graph, eades_lin_smyth(graph));
+
+    if from_els.removed.len() < from_dfs.removed.len() {
+        from_els
+    } else {
+        from_dfs
+    }
+}
+
+/// minimum_feedback_arc_set()
+///
+/// Solves every strongly connected component exactly in O(2^k · k²) for a
+/// component of k vertices.
+///
+/// Returns a minimum FeedbackArcSet, or an error if a component has more than
+/// `MAX_EXACT_COMPONENT` vertices.
+///
+pub fn minimum_feedback_arc_set(graph: &Graph) -> Result<FeedbackArcSet, FeedbackArcSetError> {
+    if !dfs(graph).is_cyclic {
+        return Ok(feedback_arc_set(graph));
+    }
+
+    let scc = strongly_connected_components(graph);
+    let mut order = Vec::with_capacity(graph.node_count);
+    for members in scc.members() {
+        if members.len() > MAX_EXACT_COMPONENT {
+            return Err(FeedbackArcSetError::ComponentTooLarge {
+                size: members.len(),
+                limit: MAX_EXACT_COMPONENT,
+            });
+        }
+        order.extend(exact_component_order(graph, &members));
+    }
+
+    // Components are in topological order, so only edges inside them can point back.
+    Ok(from_order(graph, order))
+}
+
+// Builds the feedback arc set implied by a vertex ordering.
+fn from_order(graph: &Graph, order: Vec<usize>) -> FeedbackArcSet {
+    let mut position = vec![0; graph.node_count];
+    for (i, &v) in order.iter().enumerate() {
+        position[v] = i;
+    }
+    let removed = (0..graph.node_count)
+        .flat_map(|u| graph.adj_list[u].iter().map(move |&v| (u, v)))
+        .filter(|&(u, v)| position[u] >= position[v])
+        .collect();
+    FeedbackArcSet { removed, order }
+}
+
+fn eades_lin_smyth(graph: &Graph) -> Vec<usize> {
+    let n = graph.node_count;
+    let reverse = graph.transpose();
+
+    // Degrees ignore self loops: they are removed whatever the order.
+    let mut out_degree: Vec<isize> = vec![0; n];
+    let mut in_degree: Vec<isize> = vec![0; n];
+    for (u, adj) in graph.adj_list.iter().enumerate() {
+        for &v in adj.iter().filter(|&&v| v != u) {
+            out_degree[u] += 1;
+            in_degree[v] += 1;
+        }
+    }
+
+    let mut removed = vec![false; n];
+    let mut front = Vec::with_capacity(n);
+    let mut back = Vec::new();
+    let mut remaining = n;
+
+    let take =
+        |v: usize, removed: &mut [bool], out_degree: &mut [isize], in_degree: &mut [isize]| {
+            removed[v] = true;
+            for &w in graph.adj_list[v].iter().filter(|&&w| w != v) {
+                in_degree[w] -= 1;
+            }
+            for &w in reverse.adj_list[v].iter().filter(|&&w| w != v) {
+                out_degree[w] -= 1;
+            }
+        };
+
+    while remaining > 0 {
+        let mut progressed = true;
+        while progressed {
+            progressed = false;
+            for v in 0..n {
+                if removed[v] {
+                    continue;
+                }
+                if out_degree[v] == 0 {
+                    take(v, &mut removed, &mut out_degree, &mut in_degree);
+                    back.push(v);
+                    remaining -= 1;
+                    progressed = true;
+                } else if in_degree[v] == 0 {
+                    take(v, &mut removed, &mut out_degree, &mut in_degree);
+                    front.push(v);
+                    remaining -= 1;
+                    progressed = true;
+                }
+            }
+        }
+        if let Some(v) = (0..n)
+            .filter(|&v| !removed[v])
+            .max_by_key(|&v| (out_degree[v] - in_degree[v], std::cmp::Reverse(v)))
+        {
+            take(v, &mut removed, &mut out_degree, &mut in_degree);
+            front.push(v);
+            remaining -= 1;
+        }
+    }
+
+    back.reverse();
+    front.extend(back);
+    front
+}
+
+// Orders one component so that the number of backward edges is minimal.
+// best[S] is the fewest backward edges among orderings that place exactly S first.
+fn exact_component_order(graph: &Graph, members: &[usize]) -> Vec<usize> {
+    let k = members.len();
+    if k == 1 {
+        return members.to_vec();
+    }
+    let mut index = vec![usize::MAX; graph.node_count];
+    for (i, &v) in members.iter().enumerate() {
+        index[v] = i;
+    }
+    // weight[i][j] = number of edges from members[i] to members[j].
+    let mut weight = vec![vec![0usize; k]; k];
+    for (i, &u) in members.iter().enumerate() {
+        for &v in &graph.adj_list[u] {
+            if index[v] != usize::MAX {
+                weight[i][index[v]] += 1;
+            }
+        }
+    }
+
+    let full = 1usize << k;
+    let mut best = vec![usize::MAX; full];
+    let mut last = vec![0usize; full];
+    best[0] = 0;
+    for set in 0..full {
+        if best[set] == usize::MAX {
+            continue;
+        }
+        for v in (0..k).filter(|&v| set >> v & 1 == 0) {
+            // Placing v after `set` turns its edges into `set` (and its self loops) backward.
+            let cost: usize = (0..k)
+                .filter(|&u| set >> u & 1 == 1 || u == v)
+                .map(|u| weight[v][u])
+                .sum();
+            let next = set | 1 << v;
+            if best[set] + cost < best[next] {
+                best[next] = best[set] + cost;
+                last[next] = v;
+            }
+        }
+    }
+
+    let mut order = Vec::with_capacity(k);
+    let mut set = full - 1;
+    while set != 0 {
+        let v = last[set];
+        order.push(members[v]);
+        set &= !(1 << v);
+    }
+    order.reverse();
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{graph, xorshift};
+
+    // Drops `fas.removed` (one copy per entry) and checks `fas.order` is a topological order.
+    fn leaves_valid_order(g: &Graph, fas: &FeedbackArcSet) -> bool {
+        let mut position = vec![usize::MAX; g.node_count];
+        for (i, &v) in fas.order.iter().enumerate() {
+            position[v] = i;
+        }
+        if position.contains(&usize::MAX) || fas.order.len() != g.node_count {
+            return false;
+        }
+        let mut removed = fas.removed.clone();
+        for (u, adj) in g.adj_list.iter().enumerate() {
+            for &v in adj {
+                if let Some(i) = removed.iter().position(|&e| e == (u, v)) {
+                    removed.swap_remove(i);
+                } else if position[u] >= position[v] {
+                    return false;
+                }
+            }
+        }
+        removed.is_empty()
+    }
+
+    #[test]
+    fn test_acyclic_graph() {
+        let g = graph(4, &[(0, 1), (1, 2), (0, 3)]);
+        let fas = feedback_arc_set(&g);
+        assert!(fas.removed.is_empty());
+        assert!(leaves_valid_order(&g, &fas));
+        assert_eq!(minimum_feedback_arc_set(&g).unwrap(), fas);
+    }
+
+    #[test]
+    fn test_heuristic_and_exact() {
+        // Example graph from `main`: two cycles, each broken by one edge
+        let g = graph(
+            8,
+            &[
+                (0, 1),
+                (1, 2),
+                (2, 0),
+                (2, 3),
+                (3, 4),
+                (4, 5),
+                (5, 3),
+                (6, 7),
+                (0, 4),
+            ],
+        );
+        let fas = feedback_arc_set(&g);
+        assert!(leaves_valid_order(&g, &fas));
+        assert_eq!(fas.removed.len(), 2);
+
+        let exact = minimum_feedback_arc_set(&g).unwrap();
+        assert!(leaves_valid_order(&g, &exact));
+        assert_eq!(exact.removed.len(), 2);
+    }
+
+    #[test]
+    fn test_parallel_edges_and_self_loops() {
+        // Two copies of 1 → 0 against one 0 → 1: the exact answer drops the single edge
+        let g = graph(2, &[(0, 1), (1, 0), (1, 0), (1, 1)]);
+        let exact = minimum_feedback_arc_set(&g).unwrap();
+        assert_eq!(exact.removed.len(), 2);
+        assert!(exact.removed.contains(&(0, 1)) && exact.removed.contains(&(1, 1)));
+        assert_eq!(exact.order, vec![1, 0]);
+        assert!(leaves_valid_order(&g, &feedback_arc_set(&g)));
+    }
+
+    #[test]
+    fn test_exact_never_worse_than_heuristic() {
+        let mut next = xorshift(0x0bad_5eed_1234_abcd);
+        for _ in 0..100 {
+            let n = (next() % 9 + 1) as usize;
+            let mut g = Graph::new(n);
+            for _ in 0..(next() % 25) {
+                g.add_edge((next() % n as u64) as usize, (next() % n as u64) as usize);
+            }
+            let heuristic = feedback_arc_set(&g);
+            let exact = minimum_feedback_arc_set(&g).unwrap();
+            assert!(leaves_valid_order(&g, &heuristic));
+            assert!(leaves_valid_order(&g, &exact));
+            assert!(exact.removed.len() <= heuristic.removed.len());
+        }
+    }
+
+    #[test]
+    fn test_component_too_large() {
+        let n = MAX_EXACT_COMPONENT + 1;
+        let g = graph(n, &(0..n).map(|i| (i, (i + 1) % n)).collect::<Vec<_>>());
+        assert_eq!(
+            minimum_feedback_arc_set(&g).unwrap_err(),
+            FeedbackArcSetError::ComponentTooLarge {
+                size: n,
+                limit: MAX_EXACT_COMPONENT
+            }
+        );
+        assert_eq!(feedback_arc_set(&g).removed.len(), 1);
+    }
+}
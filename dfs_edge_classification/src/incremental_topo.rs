@@ -0,0 +1,330 @@
+/*
+    Incremental Topological Order
+        the Pearce–Kelly dynamic topological sort
+
+    Inserting u → v
+        if ord(u) < ord(v) the order is already valid and nothing moves
+        otherwise only the "affected region" ord(v) ..= ord(u) can change:
+            δF = vertices reachable from v with ord ≤ ord(u)   (reaching u means a cycle)
+            δB = vertices reaching u with ord ≥ ord(v)
+        the positions held by δB ∪ δF are handed out again, all of δB first, each
+        group keeping its previous relative order
+
+    Cost
+        proportional to the edges inside the affected region, instead of the
+        O(V + E) of running `dfs` again after every insertion; the searches share
+        marks kept between insertions and clear only the vertices they visited
+*/
+
+use crate::{CycleError, Graph, dfs};
+
+/// IncrementalTopo
+///
+/// A directed acyclic graph that keeps a topological order up to date while
+/// edges are inserted one at a time.
+///
+#[derive(Debug)]
+pub struct IncrementalTopo {
+    graph: Graph,
+    reverse: Graph,
+    // position[v] is v's index in `order`.
+    position: Vec<usize>,
+    order: Vec<usize>,
+    // Search marks, all false / None between insertions.
+    seen: Vec<bool>,
+    parent: Vec<Option<usize>>,
+}
+
+impl IncrementalTopo {
+    /// new()
+    ///
+    /// Creates a graph with `num_vertices` vertices and no edges.
+    ///
+    /// Returns an instance of IncrementalTopo.
+    ///
+    pub fn new(num_vertices: usize) -> Self {
+        IncrementalTopo {
+            graph: Graph::new(num_vertices),
+            reverse: Graph::new(num_vertices),
+            position: (0..num_vertices).collect(),
+            order: (0..num_vertices).collect(),
+            seen: vec![false; num_vertices],
+            parent: vec![None; num_vertices],
+        }
+    }
+
+    /// from_graph()
+    ///
+    /// Starts from an existing graph, ordered by a single `dfs`.
+    ///
+    /// Returns an instance of IncrementalTopo, or the cycle `dfs` found.
+    ///
+    pub fn from_graph(graph: Graph) -> Result<Self, CycleError> {
+        let order = dfs(&graph).topological_order()?;
+        let mut position = vec![0; graph.node_count];
+        for (i, &v) in order.iter().enumerate() {
+            position[v] = i;
+        }
+        let n = graph.node_count;
+        Ok(IncrementalTopo {
+            reverse: graph.transpose(),
+            graph,
+            position,
+            order,
+            seen: vec![false; n],
+            parent: vec![None; n],
+        })
+    }
+
+    /// try_add_edge()
+    ///
+    /// Inserts `u → v` unless it would close a cycle, in which case the graph is
+    /// left unchanged and the error lists the cycle, starting at `v` and ending at
+    /// `u` (the rejected edge closes it).
+    ///
+    /// Returns the Result of the operation.
+    ///
+    pub fn try_add_edge(&mut self, u: usize, v: usize) -> Result<(), CycleError> {
+        assert!(
+            u < self.graph.node_count && v < self.graph.node_count,
+            "vertex out of range"
+        );
+        if u == v {
+            return Err(CycleError { cycle: vec![u] });
+        }
+
+        let (lower, upper) = (self.position[v], self.position[u]);
+        if lower < upper {
+            let forward = self.search_forward(v, u, upper)?;
+            let backward = self.search_backward(u, lower);
+            self.reorder(backward, forward);
+        }
+
+        self.graph.add_edge(u, v);
+        self.reverse.add_edge(v, u);
+        Ok(())
+    }
+
+    /// order()
+    ///
+    /// Returns the vertices in the current topological order.
+    ///
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    /// position()
+    ///
+    /// Returns the index of `v` in the current topological order.
+    ///
+    pub fn position(&self, v: usize) -> usize {
+        self.position[v]
+    }
+
+    /// graph()
+    ///
+    /// Returns the edges accepted so far.
+    ///
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    // Collects δF: vertices reachable from `start` no later than `upper` in the
+    // order. Reaching `target` means the new edge closes a cycle.
+    fn search_forward(
+        &mut self,
+        start: usize,
+        target: usize,
+        upper: usize,
+    ) -> Result<Vec<usize>, CycleError> {
+        let mut visited = vec![start];
+        self.seen[start] = true;
+        let mut stack = vec![start];
+        let mut closed_at = None;
+        'search: while let Some(w) = stack.pop() {
+            for &x in &self.graph.adj_list[w] {
+                if x == target {
+                    closed_at = Some(w);
+                    break 'search;
+                }
+                if !self.seen[x] && self.position[x] < upper {
+                    self.seen[x] = true;
+                    self.parent[x] = Some(w);
+                    visited.push(x);
+                    stack.push(x);
+                }
+            }
+        }
+
+        let cycle = closed_at.map(|w| {
+            let mut cycle = vec![target, w];
+            let mut cur = w;
+            while let Some(p) = self.parent[cur] {
+                cycle.push(p);
+                cur = p;
+            }
+            cycle.reverse();
+            cycle
+        });
+        self.clear_marks(&visited);
+        match cycle {
+            Some(cycle) => Err(CycleError { cycle }),
+            None => Ok(visited),
+        }
+    }
+
+    // Collects δB: vertices that reach `start` no earlier than `lower` in the order.
+    fn search_backward(&mut self, start: usize, lower: usize) -> Vec<usize> {
+        self.seen[start] = true;
+        let mut visited = vec![start];
+        let mut stack = vec![start];
+        while let Some(w) = stack.pop() {
+            for &x in &self.reverse.adj_list[w] {
+                if !self.seen[x] && self.position[x] > lower {
+                    self.seen[x] = true;
+                    visited.push(x);
+                    stack.push(x);
+                }
+            }
+        }
+        self.clear_marks(&visited);
+        visited
+    }
+
+    fn clear_marks(&mut self, visited: &[usize]) {
+        for &w in visited {
+            self.seen[w] = false;
+            self.parent[w] = None;
+        }
+    }
+
+    fn reorder(&mut self, mut backward: Vec<usize>, mut forward: Vec<usize>) {
+        backward.sort_unstable_by_key(|&w| self.position[w]);
+        forward.sort_unstable_by_key(|&w| self.position[w]);
+
+        let mut slots: Vec<usize> = backward
+            .iter()
+            .chain(&forward)
+            .map(|&w| self.position[w])
+            .collect();
+        slots.sort_unstable();
+
+        for (&w, slot) in backward.iter().chain(&forward).zip(slots) {
+            self.position[w] = slot;
+            self.order[slot] = w;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::xorshift;
+
+    fn is_valid(topo: &IncrementalTopo) -> bool {
+        let g = topo.graph();
+        let edges_forward = (0..g.node_count).all(|u| {
+            g.adj_list[u]
+                .iter()
+                .all(|&v| topo.position(u) < topo.position(v))
+        });
+        edges_forward && (0..g.node_count).all(|v| topo.order()[topo.position(v)] == v)
+    }
+
+    #[test]
+    fn test_reorders_on_insert() {
+        let mut topo = IncrementalTopo::new(4);
+        assert_eq!(topo.order(), &[0, 1, 2, 3]);
+
+        topo.try_add_edge(3, 1).unwrap();
+        topo.try_add_edge(1, 0).unwrap();
+        topo.try_add_edge(2, 3).unwrap();
+        assert!(is_valid(&topo));
+        assert_eq!(topo.order(), &[2, 3, 1, 0]);
+
+        // Already consistent: nothing moves
+        topo.try_add_edge(2, 0).unwrap();
+        assert_eq!(topo.order(), &[2, 3, 1, 0]);
+    }
+
+    #[test]
+    fn test_rejects_cycles() {
+        let mut topo = IncrementalTopo::new(5);
+        for (u, v) in [(0, 1), (1, 2), (2, 3), (1, 4)] {
+            topo.try_add_edge(u, v).unwrap();
+        }
+        let before = topo.order().to_vec();
+
+        assert_eq!(
+            topo.try_add_edge(3, 0),
+            Err(CycleError {
+                cycle: vec![0, 1, 2, 3]
+            })
+        );
+        assert_eq!(topo.try_add_edge(4, 4), Err(CycleError { cycle: vec![4] }));
+        assert_eq!(
+            topo.try_add_edge(2, 1),
+            Err(CycleError { cycle: vec![1, 2] })
+        );
+
+        // Rejected edges leave the graph and the order untouched
+        assert_eq!(topo.order(), before.as_slice());
+        assert_eq!(
+            topo.graph().adj_list.iter().map(|a| a.len()).sum::<usize>(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_from_graph() {
+        let mut g = Graph::new(3);
+        g.add_edge(2, 1);
+        g.add_edge(1, 0);
+        let mut topo = IncrementalTopo::from_graph(g).unwrap();
+        assert_eq!(topo.order(), &[2, 1, 0]);
+        assert_eq!(
+            topo.try_add_edge(0, 2),
+            Err(CycleError {
+                cycle: vec![2, 1, 0]
+            })
+        );
+
+        let mut g = Graph::new(2);
+        g.add_edge(0, 1);
+        g.add_edge(1, 0);
+        assert!(IncrementalTopo::from_graph(g).is_err());
+    }
+
+    #[test]
+    fn test_matches_dfs_on_random_insertions() {
+        let mut next = xorshift(0x1234_5678_9abc_def1);
+        for _ in 0..50 {
+            let n = (next() % 30 + 2) as usize;
+            let mut topo = IncrementalTopo::new(n);
+            for _ in 0..(3 * n) {
+                let (u, v) = ((next() % n as u64) as usize, (next() % n as u64) as usize);
+
+                // Ask `dfs` whether the edge would create a cycle
+                let mut probe = Graph::new(n);
+                probe.adj_list = topo.graph().adj_list.clone();
+                probe.add_edge(u, v);
+                let cyclic = dfs(&probe).is_cyclic;
+
+                match topo.try_add_edge(u, v) {
+                    Ok(()) => assert!(!cyclic),
+                    Err(CycleError { cycle }) => {
+                        assert!(cyclic);
+                        assert_eq!((cycle[0], cycle[cycle.len() - 1]), (v, u));
+                        for w in cycle.windows(2) {
+                            assert!(topo.graph().adj_list[w[0]].contains(&w[1]));
+                        }
+                    }
+                }
+                assert!(is_valid(&topo));
+                // The searches left their marks cleared for the next insertion
+                assert!(topo.seen.iter().all(|&s| !s));
+                assert!(topo.parent.iter().all(Option::is_none));
+            }
+        }
+    }
+}
@@ -0,0 +1,553 @@
+/*
+    DFS Edge Classification
+        depth-first search, its edge classes and the analyses built on them
+
+    The library holds the graph, the search and every analysis so they can be
+    used directly; the binary only wraps them in the command line interface.
+*/
+
+pub mod critical_path;
+pub mod dominators;
+pub mod edge_list;
+pub mod euler;
+pub mod feedback_arc_set;
+pub mod incremental_topo;
+pub mod reachability;
+pub mod scc;
+pub mod two_sat;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Color {
+    White,
+    Gray,
+    Black,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EdgeClass {
+    Tree,
+    Back,
+    Forward,
+    Cross,
+}
+
+#[derive(Clone, Debug)]
+pub struct EdgeInfo {
+    pub source: usize,
+    pub target: usize,
+    pub class: EdgeClass,
+}
+
+#[derive(Debug)]
+pub struct DfsResult {
+    pub discovery_time: Vec<usize>,
+    pub finish_time: Vec<usize>,
+    pub parent: Vec<Option<usize>>,
+    pub edges: Vec<EdgeInfo>,
+    pub is_cyclic: bool,
+}
+
+#[derive(Debug)]
+pub struct Graph {
+    pub node_count: usize,
+    pub adj_list: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    pub fn new(num_vertices: usize) -> Self {
+        Self { node_count: num_vertices, adj_list: vec![Vec::new(); num_vertices] }
+    }
+
+    pub fn add_edge(&mut self, u: usize, v: usize) {
+        assert!(u < self.node_count && v < self.node_count, "vertex out of range");
+        self.adj_list[u].push(v);
+    }
+
+    pub fn transpose(&self) -> Graph {
+        let mut t = Graph::new(self.node_count);
+        for (u, adj) in self.adj_list.iter().enumerate() {
+            for &v in adj {
+                t.add_edge(v, u);
+            }
+        }
+        t
+    }
+}
+
+/// ParenthesisViolation
+///
+/// A single way in which a `DfsResult` breaks the parenthesis theorem or the
+/// white-path theorem for the graph it was computed from.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParenthesisViolation {
+    TimestampOutOfRange {
+        vertex: usize,
+        time: usize,
+    },
+    DuplicateTimestamp {
+        time: usize,
+    },
+    DiscoveryAfterFinish {
+        vertex: usize,
+    },
+    ImproperNesting {
+        outer: usize,
+        inner: usize,
+    },
+    ParentMismatch {
+        vertex: usize,
+        recorded: Option<usize>,
+        enclosing: Option<usize>,
+    },
+    EdgeCountMismatch {
+        graph_edges: usize,
+        classified_edges: usize,
+    },
+    MisclassifiedEdge {
+        source: usize,
+        target: usize,
+        class: EdgeClass,
+    },
+    WhitePathViolation {
+        source: usize,
+        target: usize,
+    },
+}
+
+/// CycleError
+///
+/// Witness that a graph is not acyclic: `cycle` lists the vertices of a directed
+/// cycle in order, with an edge from the last vertex back to the first.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CycleError {
+    pub cycle: Vec<usize>,
+}
+
+impl DfsResult {
+    /// topological_order()
+    ///
+    /// Orders the vertices by decreasing finish time. Any back edge `u → v` closes
+    /// the cycle formed by the tree path from `v` down to `u`.
+    ///
+    /// Returns the topological order, or the first cycle found.
+    ///
+    pub fn topological_order(&self) -> Result<Vec<usize>, CycleError> {
+        if let Some(e) = self.edges.iter().find(|e| e.class == EdgeClass::Back) {
+            return Err(self.cycle_closed_by(e));
+        }
+
+        let mut order: Vec<usize> = (0..self.finish_time.len()).collect();
+        order.sort_unstable_by_key(|&v| std::cmp::Reverse(self.finish_time[v]));
+        Ok(order)
+    }
+
+    /// back_edge_cycles()
+    ///
+    /// Returns the cycle closed by every back edge, in the order `dfs` found them.
+    ///
+    pub fn back_edge_cycles(&self) -> Vec<CycleError> {
+        self.edges
+            .iter()
+            .filter(|e| e.class == EdgeClass::Back)
+            .map(|e| self.cycle_closed_by(e))
+            .collect()
+    }
+
+    pub fn cycle_closed_by(&self, back_edge: &EdgeInfo) -> CycleError {
+        let mut cycle = vec![back_edge.source];
+        let mut cur = back_edge.source;
+        while cur != back_edge.target {
+            cur = self.parent[cur].expect("back edge target is a tree ancestor");
+            cycle.push(cur);
+        }
+        cycle.reverse();
+        CycleError { cycle }
+    }
+
+    /// is_ancestor()
+    ///
+    /// Parenthesis theorem: `u` is a proper ancestor of `v` in the DFS forest
+    /// exactly when `[d[v], f[v]]` is nested inside `[d[u], f[u]]`.
+    ///
+    /// Returns true if `u` is a proper ancestor of `v`, in O(1).
+    ///
+    pub fn is_ancestor(&self, u: usize, v: usize) -> bool {
+        self.discovery_time[u] < self.discovery_time[v] && self.finish_time[v] < self.finish_time[u]
+    }
+
+    /// is_descendant()
+    ///
+    /// Returns true if `u` is a proper descendant of `v`, in O(1).
+    ///
+    pub fn is_descendant(&self, u: usize, v: usize) -> bool {
+        self.is_ancestor(v, u)
+    }
+
+    /// validate()
+    ///
+    /// Checks that the timestamps form a well-nested parenthesis structure that
+    /// agrees with `parent`, and that every edge of `graph` was classified in a
+    /// way the white-path theorem allows: no edge may leave a vertex while its
+    /// target is still white, and each class must match the interval relation.
+    ///
+    /// Returns every violation found, or Ok if the result is consistent.
+    ///
+    pub fn validate(&self, graph: &Graph) -> Result<(), Vec<ParenthesisViolation>> {
+        let n = self.discovery_time.len();
+        let mut violations = Vec::new();
+
+        // Every vertex owns two distinct timestamps in 1..=2n with d < f.
+        let mut at_time: Vec<Option<(usize, bool)>> = vec![None; 2 * n + 1];
+        for v in 0..n {
+            let (d, f) = (self.discovery_time[v], self.finish_time[v]);
+            for (time, is_discovery) in [(d, true), (f, false)] {
+                if time == 0 || time > 2 * n {
+                    violations.push(ParenthesisViolation::TimestampOutOfRange { vertex: v, time });
+                } else if at_time[time].is_some() {
+                    violations.push(ParenthesisViolation::DuplicateTimestamp { time });
+                } else {
+                    at_time[time] = Some((v, is_discovery));
+                }
+            }
+            if d >= f {
+                violations.push(ParenthesisViolation::DiscoveryAfterFinish { vertex: v });
+            }
+        }
+        if !violations.is_empty() {
+            return Err(violations);
+        }
+
+        // Sweep the timeline as a sequence of parentheses. The vertex on top of
+        // the stack when `v` is discovered must be its DFS parent.
+        let mut open: Vec<usize> = Vec::new();
+        for &(v, is_discovery) in at_time.iter().flatten() {
+            if is_discovery {
+                let enclosing = open.last().copied();
+                if self.parent[v] != enclosing {
+                    violations.push(ParenthesisViolation::ParentMismatch {
+                        vertex: v,
+                        recorded: self.parent[v],
+                        enclosing,
+                    });
+                }
+                open.push(v);
+            } else {
+                match open.pop() {
+                    Some(top) if top == v => {}
+                    Some(top) => {
+                        violations.push(ParenthesisViolation::ImproperNesting {
+                            outer: v,
+                            inner: top,
+                        });
+                        return Err(violations);
+                    }
+                    None => unreachable!("finish time without a matching discovery"),
+                }
+            }
+        }
+
+        // The classified edges must be exactly the edges of the graph.
+        let mut graph_edges: Vec<(usize, usize)> = (0..graph.node_count)
+            .flat_map(|u| graph.adj_list[u].iter().map(move |&v| (u, v)))
+            .collect();
+        let mut classified: Vec<(usize, usize)> =
+            self.edges.iter().map(|e| (e.source, e.target)).collect();
+        graph_edges.sort_unstable();
+        classified.sort_unstable();
+        if graph_edges != classified {
+            violations.push(ParenthesisViolation::EdgeCountMismatch {
+                graph_edges: graph_edges.len(),
+                classified_edges: classified.len(),
+            });
+        }
+
+        for e in &self.edges {
+            let (u, v) = (e.source, e.target);
+
+            // White-path: v cannot still be undiscovered when u finishes.
+            if self.discovery_time[v] > self.finish_time[u] {
+                violations.push(ParenthesisViolation::WhitePathViolation {
+                    source: u,
+                    target: v,
+                });
+                continue;
+            }
+
+            let consistent = match e.class {
+                EdgeClass::Tree => self.parent[v] == Some(u),
+                EdgeClass::Back => u == v || self.is_ancestor(v, u),
+                EdgeClass::Forward => self.is_ancestor(u, v),
+                EdgeClass::Cross => self.finish_time[v] < self.discovery_time[u],
+            };
+            if !consistent {
+                violations.push(ParenthesisViolation::MisclassifiedEdge {
+                    source: u,
+                    target: v,
+                    class: e.class,
+                });
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+pub fn dfs(graph: &Graph) -> DfsResult {
+    dfs_from_roots(graph, 0..graph.node_count)
+}
+
+// Same traversal as `dfs`, but only starts new trees from `roots`, in order.
+// Vertices not reachable from any root keep a discovery time of 0.
+pub fn dfs_from_roots(graph: &Graph, roots: impl IntoIterator<Item = usize>) -> DfsResult {
+    let n = graph.node_count;
+    let mut color = vec![Color::White; n];
+    let mut parent: Vec<Option<usize>> = vec![None; n];
+    let mut discovery_time = vec![0usize; n];
+    let mut finish_time = vec![0usize; n];
+    let mut edges: Vec<EdgeInfo> = Vec::new();
+    let mut time: usize = 0;
+    let mut is_cyclic = false;
+
+    // An explicit stack of (vertex, index of its next edge) stands in for the
+    // recursion, so long paths cannot overflow the call stack.
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    for root in roots {
+        if color[root] != Color::White {
+            continue;
+        }
+        time += 1;
+        discovery_time[root] = time;
+        color[root] = Color::Gray;
+        stack.push((root, 0));
+
+        while let Some((u, next)) = stack.last_mut() {
+            let u = *u;
+            let Some(&v) = graph.adj_list[u].get(*next) else {
+                stack.pop();
+                color[u] = Color::Black;
+                time += 1;
+                finish_time[u] = time;
+                continue;
+            };
+            *next += 1;
+
+            match color[v] {
+                Color::White => {
+                    edges.push(EdgeInfo { source: u, target: v, class: EdgeClass::Tree });
+                    parent[v] = Some(u);
+                    time += 1;
+                    discovery_time[v] = time;
+                    color[v] = Color::Gray;
+                    stack.push((v, 0));
+                }
+                Color::Gray => {
+                    // Edge to an ancestor in the DFS tree → back edge.
+                    edges.push(EdgeInfo { source: u, target: v, class: EdgeClass::Back });
+                    is_cyclic = true;
+                }
+                Color::Black => {
+                    // Finished vertex: forward if v is a descendant of u; else cross.
+                    let class = if discovery_time[u] < discovery_time[v] {
+                        EdgeClass::Forward
+                    } else {
+                        EdgeClass::Cross
+                    };
+                    edges.push(EdgeInfo { source: u, target: v, class });
+                }
+            }
+        }
+    }
+
+    DfsResult { discovery_time, finish_time, parent, edges, is_cyclic }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Deterministic xorshift64 generator for the randomized tests.
+    pub(crate) fn xorshift(mut seed: u64) -> impl FnMut() -> u64 {
+        move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        }
+    }
+
+    // Builds a graph from an edge list.
+    pub(crate) fn graph(n: usize, edges: &[(usize, usize)]) -> Graph {
+        let mut g = Graph::new(n);
+        for &(u, v) in edges {
+            g.add_edge(u, v);
+        }
+        g
+    }
+
+    fn example_graph() -> Graph {
+        let mut g = Graph::new(8);
+        g.add_edge(0, 1);
+        g.add_edge(1, 2);
+        g.add_edge(2, 0);
+        g.add_edge(2, 3);
+        g.add_edge(3, 4);
+        g.add_edge(4, 5);
+        g.add_edge(5, 3);
+        g.add_edge(6, 7);
+        g.add_edge(0, 4);
+        g
+    }
+
+    #[test]
+    fn test_ancestor_queries() {
+        let g = example_graph();
+        let result = dfs(&g);
+
+        // 0 is the root of the first DFS tree and reaches 1..=5 through tree edges
+        for v in 1..=5 {
+            assert!(result.is_ancestor(0, v));
+            assert!(result.is_descendant(v, 0));
+        }
+
+        // Ancestry is proper and does not cross trees
+        assert!(!result.is_ancestor(0, 0));
+        assert!(!result.is_ancestor(0, 6));
+        assert!(!result.is_ancestor(0, 7));
+        assert!(result.is_ancestor(6, 7));
+        assert!(!result.is_ancestor(3, 2));
+    }
+
+    #[test]
+    fn test_validate_accepts_dfs_output() {
+        let g = example_graph();
+        assert_eq!(dfs(&g).validate(&g), Ok(()));
+
+        // Self loops, parallel edges and an empty graph are all still consistent
+        let mut g = Graph::new(3);
+        g.add_edge(0, 0);
+        g.add_edge(0, 1);
+        g.add_edge(0, 1);
+        g.add_edge(2, 1);
+        assert_eq!(dfs(&g).validate(&g), Ok(()));
+        assert_eq!(dfs(&Graph::new(0)).validate(&Graph::new(0)), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_reports_violations() {
+        let g = example_graph();
+
+        // Duplicate timestamp
+        let mut result = dfs(&g);
+        result.finish_time[7] = result.finish_time[6];
+        let err = result.validate(&g).unwrap_err();
+        assert!(
+            err.iter()
+                .any(|v| matches!(v, ParenthesisViolation::DuplicateTimestamp { .. }))
+        );
+
+        // Overlapping intervals: swap the finish times of a parent and its child
+        let mut result = dfs(&g);
+        result.finish_time.swap(6, 7);
+        let err = result.validate(&g).unwrap_err();
+        assert!(err.contains(&ParenthesisViolation::ImproperNesting { outer: 6, inner: 7 }));
+
+        // Parent pointer that disagrees with the interval nesting
+        let mut result = dfs(&g);
+        result.parent[7] = None;
+        let err = result.validate(&g).unwrap_err();
+        assert!(err.contains(&ParenthesisViolation::ParentMismatch {
+            vertex: 7,
+            recorded: None,
+            enclosing: Some(6),
+        }));
+
+        // A back edge relabelled as a cross edge
+        let mut result = dfs(&g);
+        let back = result
+            .edges
+            .iter()
+            .position(|e| e.class == EdgeClass::Back)
+            .unwrap();
+        result.edges[back].class = EdgeClass::Cross;
+        let err = result.validate(&g).unwrap_err();
+        assert!(
+            err.iter()
+                .any(|v| matches!(v, ParenthesisViolation::MisclassifiedEdge { .. }))
+        );
+
+        // An edge missing from the classification
+        let mut result = dfs(&g);
+        result.edges.pop();
+        let err = result.validate(&g).unwrap_err();
+        assert!(
+            err.iter()
+                .any(|v| matches!(v, ParenthesisViolation::EdgeCountMismatch { .. }))
+        );
+    }
+
+    #[test]
+    fn test_topological_order() {
+        let mut g = Graph::new(6);
+        for (u, v) in [(5, 2), (5, 0), (4, 0), (4, 1), (2, 3), (3, 1)] {
+            g.add_edge(u, v);
+        }
+        let order = dfs(&g).topological_order().unwrap();
+        let mut position = [0; 6];
+        for (i, &v) in order.iter().enumerate() {
+            position[v] = i;
+        }
+        for (u, adj) in g.adj_list.iter().enumerate() {
+            for &v in adj {
+                assert!(position[u] < position[v]);
+            }
+        }
+
+        // The example graph's first back edge is C → A, closing A → B → C
+        let err = dfs(&example_graph()).topological_order().unwrap_err();
+        assert_eq!(
+            err,
+            CycleError {
+                cycle: vec![0, 1, 2]
+            }
+        );
+
+        let mut g = Graph::new(2);
+        g.add_edge(0, 1);
+        g.add_edge(1, 1);
+        assert_eq!(
+            dfs(&g).topological_order().unwrap_err(),
+            CycleError { cycle: vec![1] }
+        );
+    }
+
+    #[test]
+    fn test_validate_white_path() {
+        // 0 -> 1 where 1 is (wrongly) discovered only after 0 finished
+        let mut g = Graph::new(2);
+        g.add_edge(0, 1);
+        let result = DfsResult {
+            discovery_time: vec![1, 3],
+            finish_time: vec![2, 4],
+            parent: vec![None, None],
+            edges: vec![EdgeInfo {
+                source: 0,
+                target: 1,
+                class: EdgeClass::Cross,
+            }],
+            is_cyclic: false,
+        };
+        assert_eq!(
+            result.validate(&g),
+            Err(vec![ParenthesisViolation::WhitePathViolation {
+                source: 0,
+                target: 1
+            }])
+        );
+    }
+}
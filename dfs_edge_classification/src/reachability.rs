@@ -0,0 +1,213 @@
+/*
+    Reachability Index
+        transitive closure of a directed graph, stored per strongly connected component
+
+    Construction
+        1. collapse the graph into its condensation DAG (see `scc`)
+        2. walk the components in reverse topological order; the closure of a component
+           is its own bit OR-ed with the closures of its successors
+        3. every vertex answers through the bitset of its component
+
+    Cost
+        O(C · (C + E) / 64) time and O(C² / 64) words of memory for C components,
+        then O(1) per `reaches` query
+*/
+
+use crate::{
+    Graph,
+    scc::{Scc, strongly_connected_components},
+};
+
+const WORD: usize = u64::BITS as usize;
+
+/// Reachability
+///
+/// Answers "is there a path from u to v?" in O(1) after preprocessing.
+/// Every vertex reaches itself through the empty path.
+///
+#[derive(Debug)]
+pub struct Reachability {
+    scc: Scc,
+    dag: Graph,
+    closure: Vec<Vec<u64>>,
+}
+
+impl Reachability {
+    /// new()
+    ///
+    /// Builds the closure bitsets on the condensation of `graph`.
+    ///
+    /// Returns an instance of Reachability.
+    ///
+    pub fn new(graph: &Graph) -> Self {
+        let scc = strongly_connected_components(graph);
+        let dag = scc.condensation(graph);
+        let words = scc.count.div_ceil(WORD);
+
+        // Component ids are in topological order, so successors have larger ids
+        // and are complete by the time we reach their predecessors.
+        let mut closure = vec![vec![0u64; words]; scc.count];
+        for c in (0..scc.count).rev() {
+            let mut bits = vec![0u64; words];
+            bits[c / WORD] |= 1 << (c % WORD);
+            for &d in &dag.adj_list[c] {
+                for (b, &w) in bits.iter_mut().zip(&closure[d]) {
+                    *b |= w;
+                }
+            }
+            closure[c] = bits;
+        }
+
+        Reachability { scc, dag, closure }
+    }
+
+    /// reaches()
+    ///
+    /// Returns true if there is a path from `u` to `v`, in O(1).
+    ///
+    pub fn reaches(&self, u: usize, v: usize) -> bool {
+        let (cu, cv) = (self.scc.component[u], self.scc.component[v]);
+        self.closure[cu][cv / WORD] >> (cv % WORD) & 1 == 1
+    }
+
+    /// reachable_from()
+    ///
+    /// Returns every vertex reachable from `u` (including `u`), in ascending order.
+    ///
+    pub fn reachable_from(&self, u: usize) -> Vec<usize> {
+        (0..self.scc.component.len())
+            .filter(|&v| self.reaches(u, v))
+            .collect()
+    }
+
+    /// transitive_reduction()
+    ///
+    /// Builds a graph on the same vertices with the same reachability and as few
+    /// edges as possible. Between components it keeps only the condensation edges
+    /// not implied by a longer path, joining the lowest vertex of each component;
+    /// inside a component of k > 1 vertices it keeps a single k-cycle.
+    ///
+    /// Returns the reduced Graph. For an acyclic input this is the unique
+    /// transitive reduction.
+    ///
+    pub fn transitive_reduction(&self) -> Graph {
+        let members = self.scc.members();
+        let mut reduced = Graph::new(self.scc.component.len());
+
+        for m in &members {
+            if m.len() > 1 {
+                for i in 0..m.len() {
+                    reduced.add_edge(m[i], m[(i + 1) % m.len()]);
+                }
+            }
+        }
+
+        // Visiting successors in ascending (topological) order means any path
+        // a → c → b through another successor c has already been covered.
+        let words = self.scc.count.div_ceil(WORD);
+        for a in 0..self.scc.count {
+            let mut successors = self.dag.adj_list[a].clone();
+            successors.sort_unstable();
+            let mut covered = vec![0u64; words];
+            for b in successors {
+                if covered[b / WORD] >> (b % WORD) & 1 == 1 {
+                    continue;
+                }
+                reduced.add_edge(members[a][0], members[b][0]);
+                for (c, &w) in covered.iter_mut().zip(&self.closure[b]) {
+                    *c |= w;
+                }
+            }
+        }
+
+        reduced
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dfs_from_roots,
+        tests::{graph, xorshift},
+    };
+
+    fn edge_count(g: &Graph) -> usize {
+        g.adj_list.iter().map(|a| a.len()).sum()
+    }
+
+    #[test]
+    fn test_reaches() {
+        let g = graph(
+            8,
+            &[
+                (0, 1),
+                (1, 2),
+                (2, 0),
+                (2, 3),
+                (3, 4),
+                (4, 5),
+                (5, 3),
+                (6, 7),
+                (0, 4),
+            ],
+        );
+        let r = Reachability::new(&g);
+        assert!(r.reaches(0, 5));
+        assert!(r.reaches(2, 1));
+        assert!(r.reaches(4, 3));
+        assert!(!r.reaches(3, 0));
+        assert!(!r.reaches(0, 6));
+        assert!(r.reaches(6, 7) && !r.reaches(7, 6));
+        assert!(r.reaches(7, 7));
+        assert_eq!(r.reachable_from(3), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_matches_dfs_on_random_graphs() {
+        let mut next = xorshift(0xdead_beef_cafe_f00d);
+        for _ in 0..100 {
+            // Sizes past 64 exercise multi-word bitsets
+            let n = (next() % 150 + 1) as usize;
+            let m = (next() % (2 * n as u64)) as usize;
+            let mut g = Graph::new(n);
+            for _ in 0..m {
+                g.add_edge((next() % n as u64) as usize, (next() % n as u64) as usize);
+            }
+            let r = Reachability::new(&g);
+            let reduced = r.transitive_reduction();
+            let r2 = Reachability::new(&reduced);
+            assert!(edge_count(&reduced) <= edge_count(&g) + n);
+            for u in 0..n {
+                let found = dfs_from_roots(&g, [u]).discovery_time;
+                for (v, &d) in found.iter().enumerate() {
+                    assert_eq!(r.reaches(u, v), d != 0, "reaches({u}, {v})");
+                    assert_eq!(r2.reaches(u, v), r.reaches(u, v));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_transitive_reduction_of_dag() {
+        // 0 → 1 → 2 → 3 plus the shortcuts 0 → 2, 0 → 3, 1 → 3
+        let g = graph(4, &[(0, 1), (1, 2), (2, 3), (0, 2), (0, 3), (1, 3)]);
+        let reduced = Reachability::new(&g).transitive_reduction();
+        assert_eq!(reduced.adj_list, vec![vec![1], vec![2], vec![3], vec![]]);
+
+        // Diamond: no edge is redundant
+        let g = graph(4, &[(0, 1), (0, 2), (1, 3), (2, 3)]);
+        let reduced = Reachability::new(&g).transitive_reduction();
+        assert_eq!(edge_count(&reduced), 4);
+    }
+
+    #[test]
+    fn test_transitive_reduction_with_cycles() {
+        // A 3-cycle with chords and a tail
+        let g = graph(4, &[(0, 1), (1, 2), (2, 0), (0, 2), (1, 0), (2, 3), (1, 3)]);
+        let reduced = Reachability::new(&g).transitive_reduction();
+        assert_eq!(edge_count(&reduced), 4);
+        let r = Reachability::new(&reduced);
+        assert!(r.reaches(1, 0) && r.reaches(0, 3) && !r.reaches(3, 0));
+    }
+}
@@ -0,0 +1,162 @@
+/*
+    Strongly Connected Components
+        Kosaraju's algorithm on top of `dfs`
+
+    Steps
+        1. run `dfs` on the graph and record finish times
+        2. run DFS on the transpose, starting roots in decreasing finish time
+        3. every tree of the second forest is one strongly connected component
+
+    Note
+        Components are numbered in the order the second pass finds them, which is a
+        topological order of the condensation: every edge between two different
+        components goes from a lower id to a higher id.
+*/
+
+use crate::{Graph, dfs, dfs_from_roots};
+
+/// Scc
+///
+/// The strongly connected components of a directed graph.
+/// `component[v]` is the id of the component containing `v`.
+///
+#[derive(Debug)]
+pub struct Scc {
+    pub component: Vec<usize>,
+    pub count: usize,
+}
+
+impl Scc {
+    /// members()
+    ///
+    /// Groups the vertices by component.
+    ///
+    /// Returns one ascending list of vertices per component id.
+    ///
+    pub fn members(&self) -> Vec<Vec<usize>> {
+        let mut members = vec![Vec::new(); self.count];
+        for (v, &c) in self.component.iter().enumerate() {
+            members[c].push(v);
+        }
+        members
+    }
+
+    /// condensation()
+    ///
+    /// Builds the DAG with one vertex per component and one edge for every pair of
+    /// components joined by at least one edge of `graph`.
+    ///
+    /// Returns the condensation as a `Graph` without parallel edges or self loops.
+    ///
+    pub fn condensation(&self, graph: &Graph) -> Graph {
+        let mut dag = Graph::new(self.count);
+        let mut seen = vec![usize::MAX; self.count];
+        for members in self.members() {
+            for &u in &members {
+                let cu = self.component[u];
+                for &v in &graph.adj_list[u] {
+                    let cv = self.component[v];
+                    if cu != cv && seen[cv] != cu {
+                        seen[cv] = cu;
+                        dag.add_edge(cu, cv);
+                    }
+                }
+            }
+        }
+        dag
+    }
+}
+
+/// strongly_connected_components()
+///
+/// Runs Kosaraju's algorithm in O(V + E).
+///
+/// Returns the Scc of `graph`, with ids in topological order of the condensation.
+///
+pub fn strongly_connected_components(graph: &Graph) -> Scc {
+    let n = graph.node_count;
+    let first = dfs(graph);
+
+    let mut by_finish: Vec<usize> = (0..n).collect();
+    by_finish.sort_unstable_by_key(|&v| std::cmp::Reverse(first.finish_time[v]));
+
+    let second = dfs_from_roots(&graph.transpose(), by_finish.iter().copied());
+
+    // Roots of the second forest, in discovery order, are the component leaders.
+    let mut by_discovery: Vec<usize> = (0..n).collect();
+    by_discovery.sort_unstable_by_key(|&v| second.discovery_time[v]);
+
+    let mut component = vec![0; n];
+    let mut count = 0;
+    for v in by_discovery {
+        match second.parent[v] {
+            Some(p) => component[v] = component[p],
+            None => {
+                component[v] = count;
+                count += 1;
+            }
+        }
+    }
+
+    Scc { component, count }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strongly_connected_components() {
+        // Same graph as `main`: {A, B, C}, {D, E, F}, {G}, {H}
+        let mut g = Graph::new(8);
+        for (u, v) in [
+            (0, 1),
+            (1, 2),
+            (2, 0),
+            (2, 3),
+            (3, 4),
+            (4, 5),
+            (5, 3),
+            (6, 7),
+            (0, 4),
+        ] {
+            g.add_edge(u, v);
+        }
+        let scc = strongly_connected_components(&g);
+        assert_eq!(scc.count, 4);
+
+        let mut members = scc.members();
+        members.sort();
+        assert_eq!(
+            members,
+            vec![vec![0, 1, 2], vec![3, 4, 5], vec![6], vec![7]]
+        );
+
+        // Component ids follow a topological order of the condensation
+        for (u, adj) in g.adj_list.iter().enumerate() {
+            for &v in adj {
+                assert!(scc.component[u] <= scc.component[v]);
+            }
+        }
+
+        let dag = scc.condensation(&g);
+        assert_eq!(dag.node_count, 4);
+        let edges: usize = dag.adj_list.iter().map(|a| a.len()).sum();
+        assert_eq!(edges, 2);
+        assert!(!dfs(&dag).is_cyclic);
+    }
+
+    #[test]
+    fn test_single_cycle_and_empty() {
+        let mut g = Graph::new(4);
+        for (u, v) in [(0, 1), (1, 2), (2, 3), (3, 0)] {
+            g.add_edge(u, v);
+        }
+        let scc = strongly_connected_components(&g);
+        assert_eq!(scc.count, 1);
+        assert_eq!(scc.component, vec![0; 4]);
+
+        let scc = strongly_connected_components(&Graph::new(0));
+        assert_eq!(scc.count, 0);
+    }
+}
@@ -0,0 +1,487 @@
+/*
+    2-SAT
+        satisfiability of a conjunction of 2-literal clauses
+
+    Implication Graph
+        every variable x owns two vertices, x (2x) and ¬x (2x + 1)
+        a clause (a ∨ b) adds the edges ¬a → b and ¬b → a
+
+    Decision
+        the formula is unsatisfiable exactly when some x and ¬x share a strongly
+        connected component; otherwise x is set true when its component comes
+        after the component of ¬x in topological order
+*/
+
+use crate::{
+    Graph,
+    edge_list::MAX_VERTEX_NUMBER,
+    scc::{Scc, strongly_connected_components},
+};
+
+/// The most variables a DIMACS header may declare. Every variable takes two
+/// vertices of the implication graph, which stays within the vertex numbers an
+/// edge list accepts, so a one-line file cannot ask for unbounded memory.
+pub const MAX_VARIABLES: usize = MAX_VERTEX_NUMBER.div_ceil(2);
+
+/// Literal
+///
+/// A variable (0-based) or its negation.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Literal {
+    pub var: usize,
+    pub negated: bool,
+}
+
+impl Literal {
+    /// pos()
+    ///
+    /// Returns the positive literal of `var`.
+    ///
+    pub fn pos(var: usize) -> Self {
+        Literal {
+            var,
+            negated: false,
+        }
+    }
+
+    /// neg()
+    ///
+    /// Returns the negative literal of `var`.
+    ///
+    pub fn neg(var: usize) -> Self {
+        Literal { var, negated: true }
+    }
+
+    /// negate()
+    ///
+    /// Returns the complementary literal.
+    ///
+    pub fn negate(self) -> Self {
+        Literal {
+            var: self.var,
+            negated: !self.negated,
+        }
+    }
+
+    fn vertex(self) -> usize {
+        2 * self.var + self.negated as usize
+    }
+
+    fn from_vertex(v: usize) -> Self {
+        Literal {
+            var: v / 2,
+            negated: v % 2 == 1,
+        }
+    }
+}
+
+/// UnsatCore
+///
+/// Proof that the formula is unsatisfiable: `var` and its negation lie in the
+/// same strongly connected component. `to_negative` is a chain of implications
+/// from x to ¬x and `to_positive` a chain from ¬x back to x; `clauses` are the
+/// input clauses whose implications those chains use.
+///
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnsatCore {
+    pub var: usize,
+    pub to_negative: Vec<Literal>,
+    pub to_positive: Vec<Literal>,
+    pub clauses: Vec<(Literal, Literal)>,
+}
+
+/// DimacsError
+///
+/// An Error Type for all errors reading a DIMACS CNF file can produce.
+/// Line numbers are 1-based.
+///
+#[derive(Debug, PartialEq, Eq)]
+pub enum DimacsError {
+    MissingHeader,
+    InvalidHeader { line: usize },
+    TooManyVariables { line: usize, vars: usize },
+    InvalidLiteral { line: usize, token: String },
+    VariableOutOfRange { line: usize, var: usize },
+    EmptyClause { line: usize },
+    ClauseTooLong { line: usize, len: usize },
+    UnterminatedClause,
+    ClauseCountMismatch { declared: usize, found: usize },
+}
+
+/// TwoSat
+///
+/// A 2-CNF formula over `num_vars` variables, stored as its implication graph.
+///
+#[derive(Debug)]
+pub struct TwoSat {
+    num_vars: usize,
+    graph: Graph,
+    clauses: Vec<(Literal, Literal)>,
+}
+
+impl TwoSat {
+    /// new()
+    ///
+    /// Creates an empty formula over `num_vars` variables.
+    ///
+    /// Returns an instance of TwoSat.
+    ///
+    pub fn new(num_vars: usize) -> Self {
+        TwoSat {
+            num_vars,
+            graph: Graph::new(2 * num_vars),
+            clauses: Vec::new(),
+        }
+    }
+
+    /// add_clause()
+    ///
+    /// Adds the clause (a ∨ b). A unit clause (a) is written as (a ∨ a).
+    ///
+    pub fn add_clause(&mut self, a: Literal, b: Literal) {
+        assert!(
+            a.var < self.num_vars && b.var < self.num_vars,
+            "variable out of range"
+        );
+        self.graph.add_edge(a.negate().vertex(), b.vertex());
+        self.graph.add_edge(b.negate().vertex(), a.vertex());
+        self.clauses.push((a, b));
+    }
+
+    /// from_dimacs()
+    ///
+    /// Parses DIMACS CNF: `c` comment lines, a `p cnf <vars> <clauses>` header, then
+    /// clauses as signed 1-based literals terminated by `0`. Clauses may span lines
+    /// but must hold one or two literals.
+    ///
+    /// Returns the parsed formula or the first problem found.
+    ///
+    pub fn from_dimacs(input: &str) -> Result<Self, DimacsError> {
+        let mut formula: Option<TwoSat> = None;
+        let mut declared = 0;
+        let mut current: Vec<Literal> = Vec::new();
+
+        for (i, line) in input.lines().enumerate() {
+            let line_no = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('c') || line.starts_with('%') {
+                continue;
+            }
+
+            if line.starts_with('p') {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                let header = match parts.as_slice() {
+                    ["p", "cnf", vars, clauses] => vars.parse().ok().zip(clauses.parse().ok()),
+                    _ => None,
+                };
+                let Some((vars, clauses)) = header else {
+                    return Err(DimacsError::InvalidHeader { line: line_no });
+                };
+                if formula.is_some() {
+                    return Err(DimacsError::InvalidHeader { line: line_no });
+                }
+                if vars > MAX_VARIABLES {
+                    return Err(DimacsError::TooManyVariables {
+                        line: line_no,
+                        vars,
+                    });
+                }
+                formula = Some(TwoSat::new(vars));
+                declared = clauses;
+                continue;
+            }
+
+            let Some(f) = formula.as_mut() else {
+                return Err(DimacsError::MissingHeader);
+            };
+            for token in line.split_whitespace() {
+                let value: i64 = token.parse().map_err(|_| DimacsError::InvalidLiteral {
+                    line: line_no,
+                    token: token.to_string(),
+                })?;
+                if value == 0 {
+                    match current.as_slice() {
+                        [] => return Err(DimacsError::EmptyClause { line: line_no }),
+                        [a] => f.add_clause(*a, *a),
+                        [a, b] => f.add_clause(*a, *b),
+                        _ => unreachable!("long clauses are rejected as they are read"),
+                    }
+                    current.clear();
+                    continue;
+                }
+                let var = value.unsigned_abs() as usize;
+                if var > f.num_vars {
+                    return Err(DimacsError::VariableOutOfRange { line: line_no, var });
+                }
+                if current.len() == 2 {
+                    return Err(DimacsError::ClauseTooLong {
+                        line: line_no,
+                        len: 3,
+                    });
+                }
+                current.push(Literal {
+                    var: var - 1,
+                    negated: value < 0,
+                });
+            }
+        }
+
+        let formula = formula.ok_or(DimacsError::MissingHeader)?;
+        if !current.is_empty() {
+            return Err(DimacsError::UnterminatedClause);
+        }
+        if formula.clauses.len() != declared {
+            return Err(DimacsError::ClauseCountMismatch {
+                declared,
+                found: formula.clauses.len(),
+            });
+        }
+        Ok(formula)
+    }
+
+    /// solve()
+    ///
+    /// Decides satisfiability from the strongly connected components of the
+    /// implication graph in O(V + C).
+    ///
+    /// Returns a satisfying assignment indexed by variable, or an UnsatCore.
+    ///
+    pub fn solve(&self) -> Result<Vec<bool>, UnsatCore> {
+        let scc = strongly_connected_components(&self.graph);
+
+        for var in 0..self.num_vars {
+            let (x, not_x) = (Literal::pos(var).vertex(), Literal::neg(var).vertex());
+            if scc.component[x] == scc.component[not_x] {
+                return Err(self.core(&scc, var));
+            }
+        }
+
+        // Ids are in topological order, so the literal with the larger id is implied
+        // by the other one and cannot lead to a contradiction.
+        Ok((0..self.num_vars)
+            .map(|var| {
+                let (x, not_x) = (Literal::pos(var).vertex(), Literal::neg(var).vertex());
+                scc.component[x] > scc.component[not_x]
+            })
+            .collect())
+    }
+
+    /// is_satisfied_by()
+    ///
+    /// Returns true if every clause holds under `assignment`.
+    ///
+    pub fn is_satisfied_by(&self, assignment: &[bool]) -> bool {
+        let holds = |l: Literal| assignment[l.var] != l.negated;
+        self.clauses.iter().all(|&(a, b)| holds(a) || holds(b))
+    }
+
+    fn core(&self, scc: &Scc, var: usize) -> UnsatCore {
+        let (x, not_x) = (Literal::pos(var).vertex(), Literal::neg(var).vertex());
+        let to_negative = self.path_within(scc, x, not_x);
+        let to_positive = self.path_within(scc, not_x, x);
+
+        // The implication a → b comes from the clause (¬a ∨ b) or (b ∨ ¬a).
+        let mut clauses = Vec::new();
+        for chain in [&to_negative, &to_positive] {
+            for step in chain.windows(2) {
+                let (a, b) = (step[0], step[1]);
+                let clause = self
+                    .clauses
+                    .iter()
+                    .copied()
+                    .find(|&c| c == (a.negate(), b) || c == (b, a.negate()))
+                    .expect("every implication edge comes from a clause");
+                if !clauses.contains(&clause) {
+                    clauses.push(clause);
+                }
+            }
+        }
+
+        UnsatCore {
+            var,
+            to_negative,
+            to_positive,
+            clauses,
+        }
+    }
+
+    // Breadth-first search from `from` to `to` that stays inside their component.
+    fn path_within(&self, scc: &Scc, from: usize, to: usize) -> Vec<Literal> {
+        let target = scc.component[from];
+        let mut prev: Vec<Option<usize>> = vec![None; self.graph.node_count];
+        let mut queue = std::collections::VecDeque::from([from]);
+        let mut seen = vec![false; self.graph.node_count];
+        seen[from] = true;
+        while let Some(u) = queue.pop_front() {
+            if u == to {
+                break;
+            }
+            for &v in &self.graph.adj_list[u] {
+                if !seen[v] && scc.component[v] == target {
+                    seen[v] = true;
+                    prev[v] = Some(u);
+                    queue.push_back(v);
+                }
+            }
+        }
+
+        let mut path = vec![Literal::from_vertex(to)];
+        let mut cur = to;
+        while let Some(p) = prev[cur] {
+            path.push(Literal::from_vertex(p));
+            cur = p;
+        }
+        path.reverse();
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::xorshift;
+
+    #[test]
+    fn test_satisfiable() {
+        // (x0 ∨ x1) ∧ (¬x0 ∨ x2) ∧ (¬x1 ∨ ¬x2) ∧ (x0 ∨ ¬x2)
+        let mut f = TwoSat::new(3);
+        f.add_clause(Literal::pos(0), Literal::pos(1));
+        f.add_clause(Literal::neg(0), Literal::pos(2));
+        f.add_clause(Literal::neg(1), Literal::neg(2));
+        f.add_clause(Literal::pos(0), Literal::neg(2));
+
+        let assignment = f.solve().unwrap();
+        assert!(f.is_satisfied_by(&assignment));
+    }
+
+    #[test]
+    fn test_unsatisfiable_core() {
+        // (x0 ∨ x1) ∧ (x0 ∨ ¬x1) ∧ (¬x0 ∨ x1) ∧ (¬x0 ∨ ¬x1)
+        let mut f = TwoSat::new(2);
+        f.add_clause(Literal::pos(0), Literal::pos(1));
+        f.add_clause(Literal::pos(0), Literal::neg(1));
+        f.add_clause(Literal::neg(0), Literal::pos(1));
+        f.add_clause(Literal::neg(0), Literal::neg(1));
+
+        let core = f.solve().unwrap_err();
+        let x = Literal::pos(core.var);
+        assert_eq!(core.to_negative.first(), Some(&x));
+        assert_eq!(core.to_negative.last(), Some(&x.negate()));
+        assert_eq!(core.to_positive.first(), Some(&x.negate()));
+        assert_eq!(core.to_positive.last(), Some(&x));
+
+        // Every step of both chains is justified by a reported clause
+        for chain in [&core.to_negative, &core.to_positive] {
+            for step in chain.windows(2) {
+                let (a, b) = (step[0], step[1]);
+                assert!(
+                    core.clauses
+                        .iter()
+                        .any(|&c| c == (a.negate(), b) || c == (b, a.negate()))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_unit_clauses() {
+        // x0 ∧ ¬x0
+        let mut f = TwoSat::new(1);
+        f.add_clause(Literal::pos(0), Literal::pos(0));
+        assert_eq!(f.solve().unwrap(), vec![true]);
+        f.add_clause(Literal::neg(0), Literal::neg(0));
+        let core = f.solve().unwrap_err();
+        assert_eq!(core.to_negative, vec![Literal::pos(0), Literal::neg(0)]);
+        assert_eq!(core.to_positive, vec![Literal::neg(0), Literal::pos(0)]);
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let mut next = xorshift(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..300 {
+            let vars = (next() % 5 + 1) as usize;
+            let mut f = TwoSat::new(vars);
+            for _ in 0..(next() % 10) {
+                let lit = |r: u64| Literal {
+                    var: (r / 2 % vars as u64) as usize,
+                    negated: r % 2 == 1,
+                };
+                f.add_clause(lit(next()), lit(next()));
+            }
+            let brute = (0..1u32 << vars)
+                .map(|mask| (0..vars).map(|v| mask >> v & 1 == 1).collect::<Vec<_>>())
+                .any(|a| f.is_satisfied_by(&a));
+            match f.solve() {
+                Ok(a) => assert!(brute && f.is_satisfied_by(&a)),
+                Err(_) => assert!(!brute),
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_dimacs() {
+        let input = "c example\np cnf 3 4\n1 2 0\n-1 3 0\n-2 -3\n0\n1 -3 0\n";
+        let f = TwoSat::from_dimacs(input).unwrap();
+        assert!(f.is_satisfied_by(&f.solve().unwrap()));
+
+        assert_eq!(
+            TwoSat::from_dimacs("1 2 0\n").unwrap_err(),
+            DimacsError::MissingHeader
+        );
+        assert_eq!(
+            TwoSat::from_dimacs("p cnf x 1\n").unwrap_err(),
+            DimacsError::InvalidHeader { line: 1 }
+        );
+        assert_eq!(
+            TwoSat::from_dimacs("c huge\np cnf 18446744073709551615 1\n1 0\n").unwrap_err(),
+            DimacsError::TooManyVariables {
+                line: 2,
+                vars: usize::MAX
+            }
+        );
+        assert_eq!(
+            TwoSat::from_dimacs(&format!("p cnf {} 1\n1 0\n", MAX_VARIABLES + 1)).unwrap_err(),
+            DimacsError::TooManyVariables {
+                line: 1,
+                vars: MAX_VARIABLES + 1
+            }
+        );
+        let f = TwoSat::from_dimacs(&format!(
+            "p cnf {} 1\n-{} 0\n",
+            MAX_VARIABLES, MAX_VARIABLES
+        ))
+        .unwrap();
+        assert!(f.is_satisfied_by(&vec![false; MAX_VARIABLES]));
+        assert_eq!(
+            TwoSat::from_dimacs("p cnf 3 1\n1 2 3 0\n").unwrap_err(),
+            DimacsError::ClauseTooLong { line: 2, len: 3 }
+        );
+        assert_eq!(
+            TwoSat::from_dimacs("p cnf 2 1\n1 4 0\n").unwrap_err(),
+            DimacsError::VariableOutOfRange { line: 2, var: 4 }
+        );
+        assert_eq!(
+            TwoSat::from_dimacs("p cnf 2 1\n1 a 0\n").unwrap_err(),
+            DimacsError::InvalidLiteral {
+                line: 2,
+                token: "a".to_string()
+            }
+        );
+        assert_eq!(
+            TwoSat::from_dimacs("p cnf 2 1\n0\n").unwrap_err(),
+            DimacsError::EmptyClause { line: 2 }
+        );
+        assert_eq!(
+            TwoSat::from_dimacs("p cnf 2 1\n1 2\n").unwrap_err(),
+            DimacsError::UnterminatedClause
+        );
+        assert_eq!(
+            TwoSat::from_dimacs("p cnf 2 2\n1 2 0\n").unwrap_err(),
+            DimacsError::ClauseCountMismatch {
+                declared: 2,
+                found: 1
+            }
+        );
+    }
+}
@@ -0,0 +1,114 @@
+/*
+    BalancedTree Benchmarks
+        wall-clock time per operation in both balance modes
+
+    Run with `cargo bench`. Keys are inserted in ascending and in pseudo-random
+    order, then looked up and removed in pseudo-random order. The default mode
+    (α = 2/3) runs at n = 10^6. The strict mode keeps |left − right| ≤ 1, which
+    costs Θ(n) per update even on random keys (about 0.1 ms each at n = 2·10^4),
+    so it runs at n = 2·10^4 only: at n = 10^6 it would take hours.
+
+    The set operations are timed at n = 10^4 · 4^i in both modes: a union of n
+    keys with 4 new keys, repeated on the same tree, and a split in the middle of
+    a fresh tree. Their cost should grow like log n in the default mode and like
+    n in the strict mode.
+*/
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use self_balancing_bst::{BalancedTree, support::shuffled};
+
+const DEFAULT_N: u64 = 1_000_000;
+const STRICT_N: u64 = 20_000;
+const SET_OP_SIZES: [u64; 4] = [10_000, 40_000, 160_000, 640_000];
+const SET_OP_ROUNDS: u64 = 20;
+
+fn report(name: &str, ops: u64, start: Instant) {
+    let elapsed = start.elapsed();
+    println!(
+        "{:<24} {:>8.3} s  {:>8.1} ns/op",
+        name,
+        elapsed.as_secs_f64(),
+        elapsed.as_nanos() as f64 / ops as f64
+    );
+}
+
+fn run(mode: &str, n: u64, new: fn() -> BalancedTree<u64, u64>) {
+    println!("{} mode, n = {}", mode, n);
+    let random = shuffled(n);
+
+    let start = Instant::now();
+    let mut ascending = new();
+    for key in 0..n {
+        ascending.insert(key, key);
+    }
+    report("insert ascending", n, start);
+    drop(black_box(ascending));
+
+    let start = Instant::now();
+    let mut tree = new();
+    for &key in &random {
+        tree.insert(key, key);
+    }
+    report("insert random", n, start);
+
+    let start = Instant::now();
+    for &key in &random {
+        black_box(tree.get(&key));
+    }
+    report("get random", n, start);
+
+    let start = Instant::now();
+    for &key in random.iter().rev() {
+        black_box(tree.remove(&key));
+    }
+    report("remove random", n, start);
+    assert!(tree.is_empty());
+}
+
+fn set_ops(mode: &str, new: fn() -> BalancedTree<u64, u64>) {
+    println!("{} mode, set operations", mode);
+    for n in SET_OP_SIZES {
+        // A union into an empty tree adopts the other tree in O(n), in `new`'s mode
+        let build = || new().union(BalancedTree::from_sorted((0..n).map(|k| (2 * k, k))));
+        let (mut union, mut split) = (Duration::ZERO, Duration::ZERO);
+        let mut tree = build();
+        for round in 0..SET_OP_ROUNDS {
+            let odd: BalancedTree<u64, u64> =
+                (0..4).map(|i| (2 * (i * n / 4 + round) + 1, 0)).collect();
+            let start = Instant::now();
+            tree = tree.union(odd);
+            union += start.elapsed();
+        }
+        drop(black_box(tree));
+        for round in 0..SET_OP_ROUNDS {
+            let tree = build();
+            let start = Instant::now();
+            let halves = tree.split_at(&(n + 2 * round));
+            split += start.elapsed();
+            drop(black_box(halves));
+        }
+        let per_op = |total: Duration| total.as_nanos() as f64 / 1000.0 / SET_OP_ROUNDS as f64;
+        println!(
+            "n = {:<8} union with 4 keys {:>9.1} µs   split_at {:>9.1} µs",
+            n,
+            per_op(union),
+            per_op(split)
+        );
+    }
+}
+
+fn main() {
+    let start = Instant::now();
+    let bulk = BalancedTree::from_sorted((0..DEFAULT_N).map(|key| (key, key)));
+    report("from_sorted", DEFAULT_N, start);
+    drop(black_box(bulk));
+
+    run("default", DEFAULT_N, BalancedTree::new);
+    run("strict", STRICT_N, BalancedTree::strict);
+    set_ops("default", BalancedTree::new);
+    set_ops("strict", BalancedTree::strict);
+}
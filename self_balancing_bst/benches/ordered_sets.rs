@@ -0,0 +1,77 @@
+/*
+    OrderedSet Comparison
+        BalancedTree against AVL, red-black, treap and splay trees
+
+    Run with `cargo bench --bench ordered_sets`. For each strategy and input order,
+    N keys are inserted, looked up and deleted; the table shows time per operation,
+    rotations per operation and the height after the inserts. BalancedTree runs in
+    its default mode (α = 2/3), the one with amortized Θ(log n) updates, with stats
+    on so that its rotation columns count the nodes relinked by rebuilds.
+*/
+
+use std::{hint::black_box, time::Instant};
+
+use self_balancing_bst::{
+    BalancedTree, avl::AvlTree, ordered_set::OrderedSet, red_black::RedBlackTree, splay::SplayTree,
+    support::shuffled, treap::Treap,
+};
+
+const N: u64 = 200_000;
+
+fn run<S: OrderedSet<u64>>(name: &str, order: &str, mut set: S, keys: &[u64], lookups: &[u64]) {
+    let n = keys.len() as f64;
+    let per_op = |start: Instant| start.elapsed().as_nanos() as f64 / n;
+
+    let start = Instant::now();
+    for &key in keys {
+        set.insert(key);
+    }
+    let insert_ns = per_op(start);
+    let insert_rotations = set.rotations();
+    let height = set.height();
+
+    let start = Instant::now();
+    for key in lookups {
+        black_box(set.contains(key));
+    }
+    let contains_ns = per_op(start);
+
+    let before = set.rotations();
+    let start = Instant::now();
+    for key in lookups {
+        set.delete(key);
+    }
+    let delete_ns = per_op(start);
+    assert!(set.is_empty());
+
+    println!(
+        "{:<13} {:<10} {:>7} {:>10.1} {:>9.2} {:>10.1} {:>10.1} {:>9.2}",
+        name,
+        order,
+        height,
+        insert_ns,
+        insert_rotations as f64 / n,
+        contains_ns,
+        delete_ns,
+        (set.rotations() - before) as f64 / n,
+    );
+}
+
+fn main() {
+    let random = shuffled(N);
+    let ascending: Vec<u64> = (0..N).collect();
+    println!("n = {}", N);
+    println!(
+        "{:<13} {:<10} {:>7} {:>10} {:>9} {:>10} {:>10} {:>9}",
+        "strategy", "order", "height", "ins ns/op", "ins rot", "get ns/op", "del ns/op", "del rot"
+    );
+    for (order, keys) in [("random", &random), ("ascending", &ascending)] {
+        let mut tree = BalancedTree::<u64, ()>::new();
+        tree.enable_stats();
+        run("BalancedTree", order, tree, keys, &random);
+        run("AVL", order, AvlTree::new(), keys, &random);
+        run("red-black", order, RedBlackTree::new(), keys, &random);
+        run("treap", order, Treap::new(), keys, &random);
+        run("splay", order, SplayTree::new(), keys, &random);
+    }
+}
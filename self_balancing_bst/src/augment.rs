@@ -0,0 +1,321 @@
+/*
+    Augmentations
+        a summary of every subtree, kept next to the counts
+
+    The subtree counts are one example of data a node can keep about its whole
+    subtree: they are cheap to recompute from the children and answer rank and
+    select in O(log n). `Augment` lets the user choose more of it. A summary must
+    form a monoid over the entries in key order:
+
+        empty()            the summary of no entries
+        single(k, v)       the summary of one entry
+        combine(a, b)      the summary of a's entries followed by b's
+
+    where `combine` is associative and `empty()` is its identity. It need not be
+    commutative; entries are always combined from the smallest key up.
+
+    Every insert, delete, rebuild, split and join refreshes the summaries on the
+    nodes it touches, so a query over a key range can combine O(log n) stored
+    summaries instead of visiting every entry.
+*/
+
+use std::ops::{Add, Bound, RangeBounds};
+
+use crate::{BalancedTree, TreeNode};
+
+/// Augment
+///
+/// A monoid of summaries over a tree's entries. Implementors are marker types;
+/// the tree calls the functions below and stores only `Summary` values.
+///
+pub trait Augment<K, V> {
+    type Summary: Clone + PartialEq;
+
+    /// The summary of no entries, and the identity of `combine`.
+    fn empty() -> Self::Summary;
+
+    /// The summary of a single entry.
+    fn single(key: &K, value: &V) -> Self::Summary;
+
+    /// The summary of `left`'s entries followed by `right`'s.
+    fn combine(left: &Self::Summary, right: &Self::Summary) -> Self::Summary;
+}
+
+/// No augmentation beyond the counts; the default for `BalancedTree`.
+impl<K, V> Augment<K, V> for () {
+    type Summary = ();
+
+    fn empty() {}
+
+    fn single(_: &K, _: &V) {}
+
+    fn combine(_: &(), _: &()) {}
+}
+
+/// Sum
+///
+/// Total of the values, starting from `V::default()`.
+///
+pub struct Sum;
+
+impl<K, V: Clone + PartialEq + Default + Add<Output = V>> Augment<K, V> for Sum {
+    type Summary = V;
+
+    fn empty() -> V {
+        V::default()
+    }
+
+    fn single(_: &K, value: &V) -> V {
+        value.clone()
+    }
+
+    fn combine(left: &V, right: &V) -> V {
+        left.clone() + right.clone()
+    }
+}
+
+/// Min
+///
+/// Smallest value, or None for no entries.
+///
+pub struct Min;
+
+impl<K, V: Clone + Ord> Augment<K, V> for Min {
+    type Summary = Option<V>;
+
+    fn empty() -> Option<V> {
+        None
+    }
+
+    fn single(_: &K, value: &V) -> Option<V> {
+        Some(value.clone())
+    }
+
+    fn combine(left: &Option<V>, right: &Option<V>) -> Option<V> {
+        match (left, right) {
+            (Some(l), Some(r)) => Some(l.min(r).clone()),
+            _ => left.clone().or_else(|| right.clone()),
+        }
+    }
+}
+
+/// Max
+///
+/// Largest value, or None for no entries.
+///
+pub struct Max;
+
+impl<K, V: Clone + Ord> Augment<K, V> for Max {
+    type Summary = Option<V>;
+
+    fn empty() -> Option<V> {
+        None
+    }
+
+    fn single(_: &K, value: &V) -> Option<V> {
+        Some(value.clone())
+    }
+
+    fn combine(left: &Option<V>, right: &Option<V>) -> Option<V> {
+        match (left, right) {
+            (Some(l), Some(r)) => Some(l.max(r).clone()),
+            _ => left.clone().or_else(|| right.clone()),
+        }
+    }
+}
+
+impl<K: Ord, V, A: Augment<K, V>> BalancedTree<K, V, A> {
+    pub fn summary(&self) -> A::Summary {
+        /*
+            The summary of the whole tree, read from the root in O(1).
+        */
+        self.root
+            .as_ref()
+            .map_or_else(A::empty, |r| r.summary.clone())
+    }
+
+    pub fn range_summary<R: RangeBounds<K>>(&self, range: R) -> A::Summary {
+        /*
+            The summary of the entries whose keys fall inside `range`, e.g. `a..b`,
+            `..=b` or `a..`. An empty or inverted range gives `A::empty()`.
+
+            Descends until the paths to the two bounds part, then follows each
+            bound's path and takes the subtrees hanging inside the range whole, so
+            it combines O(height) = O(log n) summaries.
+        */
+        fn summarize<K: Ord, V, A: Augment<K, V>>(
+            node: &Option<Box<TreeNode<K, V, A::Summary>>>,
+            low: Bound<&K>,
+            high: Bound<&K>,
+        ) -> A::Summary {
+            let Some(n) = node else {
+                return A::empty();
+            };
+            if let (Bound::Unbounded, Bound::Unbounded) = (low, high) {
+                return n.summary.clone();
+            }
+            let above_low = match low {
+                Bound::Included(x) => n.key >= *x,
+                Bound::Excluded(x) => n.key > *x,
+                Bound::Unbounded => true,
+            };
+            let below_high = match high {
+                Bound::Included(x) => n.key <= *x,
+                Bound::Excluded(x) => n.key < *x,
+                Bound::Unbounded => true,
+            };
+            if !above_low {
+                return summarize::<K, V, A>(&n.right, low, high);
+            }
+            if !below_high {
+                return summarize::<K, V, A>(&n.left, low, high);
+            }
+            // n is inside the range: everything left of it only needs the low
+            // bound, everything right of it only the high bound
+            let left = summarize::<K, V, A>(&n.left, low, Bound::Unbounded);
+            let right = summarize::<K, V, A>(&n.right, Bound::Unbounded, high);
+            A::combine(&A::combine(&left, &A::single(&n.key, &n.value)), &right)
+        }
+
+        summarize::<K, V, A>(&self.root, range.start_bound(), range.end_bound())
+    }
+}
+
+impl<K: Ord, V: Clone + PartialEq + Default + Add<Output = V>> BalancedTree<K, V, Sum> {
+    pub fn range_sum<R: RangeBounds<K>>(&self, range: R) -> V {
+        /*
+            Total of the values whose keys fall inside `range`, in O(log n).
+        */
+        self.range_summary(range)
+    }
+}
+
+impl<K: Ord, V: Clone + Ord> BalancedTree<K, V, Min> {
+    pub fn range_min<R: RangeBounds<K>>(&self, range: R) -> Option<V> {
+        /*
+            Smallest value whose key falls inside `range`, in O(log n).
+        */
+        self.range_summary(range)
+    }
+}
+
+impl<K: Ord, V: Clone + Ord> BalancedTree<K, V, Max> {
+    pub fn range_max<R: RangeBounds<K>>(&self, range: R) -> Option<V> {
+        /*
+            Largest value whose key falls inside `range`, in O(log n).
+        */
+        self.range_summary(range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{assert_valid, xorshift};
+    use std::collections::BTreeMap;
+
+    // Keys in order: not commutative, so it also checks combine's argument order
+    struct Keys;
+
+    impl<V> Augment<u32, V> for Keys {
+        type Summary = Vec<u32>;
+
+        fn empty() -> Vec<u32> {
+            Vec::new()
+        }
+
+        fn single(key: &u32, _: &V) -> Vec<u32> {
+            vec![*key]
+        }
+
+        fn combine(left: &Vec<u32>, right: &Vec<u32>) -> Vec<u32> {
+            left.iter().chain(right).copied().collect()
+        }
+    }
+
+    #[test]
+    fn test_running_totals_over_events() {
+        // Amounts booked at timestamps, some of them corrected later
+        let mut ledger = BalancedTree::with_augment(Sum);
+        for (time, amount) in [(10, 5), (20, -2), (30, 7), (40, 1), (50, 4)] {
+            ledger.insert(time, amount);
+            assert_valid(&ledger);
+        }
+        assert_eq!(ledger.summary(), 15);
+        assert_eq!(ledger.range_sum(..=30), 10);
+        assert_eq!(ledger.range_sum(20..50), 6);
+        assert_eq!(ledger.range_sum(41..), 4);
+        assert_eq!(ledger.range_sum(60..), 0);
+
+        assert_eq!(ledger.insert(20, 3), Some(-2));
+        assert_valid(&ledger);
+        assert_eq!(ledger.range_sum(..=30), 15);
+        assert_eq!(ledger.remove(&30), Some(7));
+        assert_valid(&ledger);
+        assert_eq!(ledger.range_sum(..=30), 8);
+        assert!(ledger.update(&40, |amount| *amount += 10));
+        assert!(!ledger.update(&45, |_| unreachable!()));
+        assert_valid(&ledger);
+        assert_eq!(ledger.range_sum(35..), 15);
+        assert_eq!(ledger.range_sum(..), ledger.summary());
+    }
+
+    #[test]
+    fn test_min_max_and_empty_ranges() {
+        let readings: Vec<(u32, i32)> = (0..100).map(|t| (t, (t as i32 * 37) % 101 - 50)).collect();
+        let lows: BalancedTree<u32, i32, Min> = readings.iter().copied().collect();
+        let highs: BalancedTree<u32, i32, Max> = readings.iter().copied().collect();
+        assert_valid(&lows);
+        assert_valid(&highs);
+        let values = |lo: usize, hi: usize| readings[lo..hi].iter().map(|&(_, v)| v);
+        assert_eq!(lows.range_min(10..20), values(10, 20).min());
+        assert_eq!(highs.range_max(10..20), values(10, 20).max());
+        assert_eq!(lows.range_min(..), values(0, 100).min());
+        assert_eq!(lows.range_min(50..50), None);
+        assert_eq!(
+            highs.range_max((Bound::Excluded(30), Bound::Included(10))),
+            None
+        );
+        assert_eq!(BalancedTree::<u32, i32, Max>::default().range_max(..), None);
+    }
+
+    #[test]
+    fn test_summaries_survive_every_update() {
+        let mut next = xorshift(0x00c0_ffee_1234_5678);
+        for strict in [false, true] {
+            let mut tree = match strict {
+                true => BalancedTree::strict_with_augment(Keys),
+                false => BalancedTree::with_augment(Keys),
+            };
+            let mut model = BTreeMap::new();
+            for step in 0..2000 {
+                let key = (next() % 200) as u32;
+                match next() % 5 {
+                    0 | 1 => assert_eq!(tree.insert(key, ()), model.insert(key, ())),
+                    2 => assert_eq!(tree.remove(&key), model.remove(&key)),
+                    3 => assert_eq!(
+                        tree.pop_first().map(|e| e.0),
+                        model.pop_first().map(|e| e.0)
+                    ),
+                    _ => {
+                        // split and join back together
+                        let (low, high) = tree.split_at(&key);
+                        tree = BalancedTree::join(low, high);
+                    }
+                }
+                assert_valid(&tree);
+                if step % 50 == 0 {
+                    let (a, b) = ((next() % 220) as u32, (next() % 220) as u32);
+                    let range = a.min(b)..a.max(b);
+                    let expected: Vec<u32> = model.range(range.clone()).map(|(k, _)| *k).collect();
+                    assert_eq!(tree.range_summary(range), expected);
+                    assert_eq!(tree.summary(), model.keys().copied().collect::<Vec<_>>());
+                }
+            }
+            let other: BalancedTree<u32, (), Keys> = (100..300).map(|k| (k, ())).collect();
+            let union = tree.union(other);
+            assert_valid(&union);
+            assert_eq!(union.summary(), union.keys().copied().collect::<Vec<_>>());
+        }
+    }
+}
@@ -0,0 +1,264 @@
+/*
+    AVL Tree
+        height-balanced BST
+
+    Invariant
+        at every node the heights of the two subtrees differ by at most 1,
+        which bounds the height by about 1.44 · log2(n + 2)
+
+    Updates
+        after an insert or delete, every node on the path back to the root
+        recomputes its height and, if its balance factor reached ±2, is fixed
+        with a single or double rotation
+*/
+
+use std::cmp::Ordering;
+
+use crate::ordered_set::{BinaryNode, InOrder, OrderedSet};
+
+type Link<K> = Option<Box<Node<K>>>;
+
+struct Node<K> {
+    key: K,
+    height: usize,
+    left: Link<K>,
+    right: Link<K>,
+}
+
+impl<K> BinaryNode for Node<K> {
+    type Key = K;
+
+    fn key(&self) -> &K {
+        &self.key
+    }
+
+    fn left(&self) -> Option<&Self> {
+        self.left.as_deref()
+    }
+
+    fn right(&self) -> Option<&Self> {
+        self.right.as_deref()
+    }
+}
+
+fn height<K>(node: &Link<K>) -> usize {
+    node.as_ref().map_or(0, |n| n.height)
+}
+
+impl<K> Node<K> {
+    fn update(&mut self) {
+        self.height = 1 + height(&self.left).max(height(&self.right));
+    }
+
+    fn balance_factor(&self) -> isize {
+        height(&self.left) as isize - height(&self.right) as isize
+    }
+}
+
+/// AvlTree
+///
+/// An OrderedSet kept balanced by subtree heights.
+///
+pub struct AvlTree<K> {
+    root: Link<K>,
+    len: usize,
+    rotations: u64,
+}
+
+impl<K: Ord> Default for AvlTree<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord> AvlTree<K> {
+    /// new()
+    ///
+    /// Returns an empty AvlTree.
+    ///
+    pub fn new() -> Self {
+        AvlTree {
+            root: None,
+            len: 0,
+            rotations: 0,
+        }
+    }
+
+    fn rotate_right(&mut self, mut node: Box<Node<K>>) -> Box<Node<K>> {
+        self.rotations += 1;
+        let mut pivot = node.left.take().expect("rotate_right needs a left child");
+        node.left = pivot.right.take();
+        node.update();
+        pivot.right = Some(node);
+        pivot.update();
+        pivot
+    }
+
+    fn rotate_left(&mut self, mut node: Box<Node<K>>) -> Box<Node<K>> {
+        self.rotations += 1;
+        let mut pivot = node.right.take().expect("rotate_left needs a right child");
+        node.right = pivot.left.take();
+        node.update();
+        pivot.left = Some(node);
+        pivot.update();
+        pivot
+    }
+
+    // Restores the height and the AVL condition at `node`, whose subtrees are valid.
+    fn rebalance(&mut self, mut node: Box<Node<K>>) -> Box<Node<K>> {
+        node.update();
+        let factor = node.balance_factor();
+        if factor > 1 {
+            if node.left.as_ref().is_some_and(|l| l.balance_factor() < 0) {
+                let left = node.left.take().unwrap();
+                node.left = Some(self.rotate_left(left));
+            }
+            return self.rotate_right(node);
+        }
+        if factor < -1 {
+            if node.right.as_ref().is_some_and(|r| r.balance_factor() > 0) {
+                let right = node.right.take().unwrap();
+                node.right = Some(self.rotate_right(right));
+            }
+            return self.rotate_left(node);
+        }
+        node
+    }
+
+    fn insert_in(&mut self, node: Link<K>, key: K, inserted: &mut bool) -> Box<Node<K>> {
+        let Some(mut n) = node else {
+            *inserted = true;
+            return Box::new(Node {
+                key,
+                height: 1,
+                left: None,
+                right: None,
+            });
+        };
+        match key.cmp(&n.key) {
+            Ordering::Less => n.left = Some(self.insert_in(n.left.take(), key, inserted)),
+            Ordering::Greater => n.right = Some(self.insert_in(n.right.take(), key, inserted)),
+            Ordering::Equal => return n,
+        }
+        self.rebalance(n)
+    }
+
+    // Unlinks the minimum of a subtree, returning the rest and the removed key.
+    fn remove_min(&mut self, mut node: Box<Node<K>>) -> (Link<K>, K) {
+        match node.left.take() {
+            None => (node.right.take(), node.key),
+            Some(left) => {
+                let (rest, min) = self.remove_min(left);
+                node.left = rest;
+                (Some(self.rebalance(node)), min)
+            }
+        }
+    }
+
+    fn delete_in(&mut self, node: Link<K>, key: &K, deleted: &mut bool) -> Link<K> {
+        let mut n = node?;
+        match key.cmp(&n.key) {
+            Ordering::Less => n.left = self.delete_in(n.left.take(), key, deleted),
+            Ordering::Greater => n.right = self.delete_in(n.right.take(), key, deleted),
+            Ordering::Equal => {
+                *deleted = true;
+                match (n.left.take(), n.right.take()) {
+                    (None, child) | (child, None) => return child,
+                    (Some(left), Some(right)) => {
+                        let (rest, successor) = self.remove_min(right);
+                        n.key = successor;
+                        n.left = Some(left);
+                        n.right = rest;
+                    }
+                }
+            }
+        }
+        Some(self.rebalance(n))
+    }
+}
+
+impl<K: Ord> OrderedSet<K> for AvlTree<K> {
+    fn insert(&mut self, key: K) -> bool {
+        let mut inserted = false;
+        let root = self.root.take();
+        self.root = Some(self.insert_in(root, key, &mut inserted));
+        self.len += inserted as usize;
+        inserted
+    }
+
+    fn delete(&mut self, key: &K) -> bool {
+        let mut deleted = false;
+        let root = self.root.take();
+        self.root = self.delete_in(root, key, &mut deleted);
+        self.len -= deleted as usize;
+        deleted
+    }
+
+    fn contains(&mut self, key: &K) -> bool {
+        let mut cur = self.root.as_deref();
+        while let Some(node) = cur {
+            cur = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Greater => node.right.as_deref(),
+                Ordering::Equal => return true,
+            };
+        }
+        false
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &K> + '_> {
+        Box::new(InOrder::new(self.root.as_deref()))
+    }
+
+    fn height(&self) -> usize {
+        height(&self.root)
+    }
+
+    fn rotations(&self) -> u64 {
+        self.rotations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ordered_set::tests::conformance;
+
+    // Checks ordering, stored heights and the AVL condition.
+    fn check<K: Ord>(tree: &AvlTree<K>) {
+        fn walk<K: Ord>(node: &Link<K>, low: Option<&K>, high: Option<&K>) -> (usize, usize) {
+            let Some(n) = node else {
+                return (0, 0);
+            };
+            assert!(low.is_none_or(|l| *l < n.key) && high.is_none_or(|h| n.key < *h));
+            let (ls, lh) = walk(&n.left, low, Some(&n.key));
+            let (rs, rh) = walk(&n.right, Some(&n.key), high);
+            assert_eq!(n.height, 1 + lh.max(rh));
+            assert!(lh.abs_diff(rh) <= 1);
+            (1 + ls + rs, n.height)
+        }
+        assert_eq!(walk(&tree.root, None, None).0, tree.len);
+    }
+
+    #[test]
+    fn test_conformance() {
+        conformance(AvlTree::new, check);
+    }
+
+    #[test]
+    fn test_double_rotation() {
+        let mut tree = AvlTree::new();
+        for key in [30, 10, 20] {
+            tree.insert(key);
+            check(&tree);
+        }
+        // Left-right case: one rotation at 10, one at 30
+        assert_eq!(tree.rotations(), 2);
+        assert_eq!(tree.root.as_ref().unwrap().key, 20);
+        assert_eq!(tree.height(), 2);
+    }
+}
@@ -0,0 +1,75 @@
+/*
+    Growth Measurements
+        how the work per insert and the height grow with n
+
+    Run with `cargo run --release --bin growth`. For n = 2^k, keys 0..n are
+    inserted in sorted, reverse and pseudo-random order into a tree with stats
+    enabled, once in the default mode and once in the strict mode. Each row
+    shows, per insert, the nodes visited, subtree rebuilds and nodes relinked by
+    rebuilds, then the final and the largest height seen, the final height
+    divided by log2(n), and the most the height the stats report after any
+    insert exceeded the least possible height of ⌈log2(size + 1)⌉.
+
+    The stats cost O(1) per insert, heights included, so n is limited only by
+    the inserts themselves: k runs to 20 in the default mode, but only to 14 in
+    the strict mode, where keeping |left − right| ≤ 1 costs up to Θ(n) per
+    insert.
+*/
+
+use self_balancing_bst::{BalancedTree, support::shuffled};
+
+const MAX_K_DEFAULT: u32 = 20;
+const MAX_K_STRICT: u32 = 14;
+
+fn measure(mode: &str, order: &str, mut tree: BalancedTree<u64, ()>, keys: &[u64]) {
+    tree.enable_stats();
+    let mut excess = 0;
+    for (i, &key) in keys.iter().enumerate() {
+        tree.insert(key, ());
+        // ⌈log2(size + 1)⌉ is the bit length of the size
+        let least = (usize::BITS - (i + 1).leading_zeros()) as usize;
+        excess = excess.max(tree.stats().expect("stats enabled").height - least);
+    }
+    let stats = tree.disable_stats().expect("stats enabled");
+    println!(
+        "{:<7} {:<8} {:>7} {:>8.2} {:>9.4} {:>9.2} {:>6} {:>6} {:>7.2} {:>6}",
+        mode,
+        order,
+        keys.len(),
+        stats.per_update(stats.node_visits),
+        stats.per_update(stats.rebuilds),
+        stats.per_update(stats.nodes_rebuilt),
+        stats.height,
+        stats.max_height,
+        stats.height as f64 / (keys.len() as f64).log2(),
+        excess,
+    );
+}
+
+fn main() {
+    println!(
+        "{:<7} {:<8} {:>7} {:>8} {:>9} {:>9} {:>6} {:>6} {:>7} {:>6}",
+        "mode", "order", "n", "visits", "rebuilds", "relinked", "height", "max", "h/lg n", "excess"
+    );
+    for strict in [false, true] {
+        let mode = if strict { "strict" } else { "default" };
+        let max_k = if strict { MAX_K_STRICT } else { MAX_K_DEFAULT };
+        for k in 1..=max_k {
+            let n = 1u64 << k;
+            let sorted: Vec<u64> = (0..n).collect();
+            let reverse: Vec<u64> = (0..n).rev().collect();
+            for (order, keys) in [
+                ("sorted", sorted),
+                ("reverse", reverse),
+                ("random", shuffled(n)),
+            ] {
+                let tree = if strict {
+                    BalancedTree::strict()
+                } else {
+                    BalancedTree::new()
+                };
+                measure(mode, order, tree, &keys);
+            }
+        }
+    }
+}
@@ -0,0 +1,543 @@
+/*
+    Cursor
+        a position in a BalancedTree that can move to its neighbours and delete
+
+    The cursor takes the tree apart along the path from the root to its entry: it
+    owns every node on that path, each with its child on the path taken out, and
+    puts the tree back together when it is dropped. The cached counts stay valid
+    throughout, since no subtree changes size by being detached.
+
+        current entry          O(1), it is the node at the end of the path
+        move_next / move_prev  O(1) amortized over a walk, O(log n) at worst
+        remove_current         O(log n) plus at most one rebuild, like `remove`
+
+    Besides one position per entry there is a "ghost" position past the last
+    entry, where `key()` is None. Moving next from the last entry or previous
+    from the first lands on the ghost, and moving from the ghost wraps around:
+
+        ghost → first → ... → last → ghost
+*/
+
+use std::cmp::Ordering;
+
+use crate::{BalancedTree, TreeNode, augment::Augment, observer::Op};
+
+type Link<K, V, A> = Option<Box<TreeNode<K, V, <A as Augment<K, V>>::Summary>>>;
+// Ancestors top down, each with the side its child on the path hangs from
+type Path<K, V, A> = Vec<(Box<TreeNode<K, V, <A as Augment<K, V>>::Summary>>, Side)>;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    fn other(self) -> Side {
+        match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+        }
+    }
+
+    fn of<K, V, S>(self, node: &TreeNode<K, V, S>) -> &Option<Box<TreeNode<K, V, S>>> {
+        match self {
+            Side::Left => &node.left,
+            Side::Right => &node.right,
+        }
+    }
+}
+
+// A tree taken apart along one path from the root. `slot` holds the subtree at
+// the end of the path, or None where a search ran off the tree; `path` holds its
+// ancestors top down, each missing the child on the path. Dropping the zipper
+// (or `into_tree`) puts the tree back together.
+pub(crate) struct Zipper<'a, K, V, A: Augment<K, V>> {
+    tree: Option<&'a mut BalancedTree<K, V, A>>,
+    path: Path<K, V, A>,
+    pub(crate) slot: Link<K, V, A>,
+}
+
+impl<K, V, A: Augment<K, V>> Drop for Zipper<'_, K, V, A> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+impl<'a, K, V, A: Augment<K, V>> Zipper<'a, K, V, A> {
+    pub(crate) fn open(tree: &'a mut BalancedTree<K, V, A>) -> Self {
+        /*
+            Start at the root, with an empty path.
+        */
+        let slot = tree.root.take();
+        Self {
+            tree: Some(tree),
+            path: Vec::new(),
+            slot,
+        }
+    }
+
+    pub(crate) fn into_tree(mut self) -> &'a mut BalancedTree<K, V, A> {
+        /*
+            Put the tree back together and hand back the borrow it came from.
+        */
+        self.close();
+        self.tree.take().expect("zipper is open")
+    }
+
+    fn close(&mut self) {
+        self.climb_to_root();
+        if let Some(tree) = self.tree.as_deref_mut() {
+            tree.root = self.slot.take();
+        }
+    }
+
+    fn descend(&mut self, side: Side) {
+        /*
+            Move the slot to a child of the node in it.
+        */
+        let mut node = self.slot.take().expect("descend from an empty slot");
+        self.slot = match side {
+            Side::Left => node.left.take(),
+            Side::Right => node.right.take(),
+        };
+        self.path.push((node, side));
+    }
+
+    fn ascend(&mut self) -> Option<Side> {
+        /*
+            Move the slot to its parent, reattaching it, and return which child it
+            was; None at the root.
+        */
+        let (mut parent, side) = self.path.pop()?;
+        match side {
+            Side::Left => parent.left = self.slot.take(),
+            Side::Right => parent.right = self.slot.take(),
+        }
+        self.slot = Some(parent);
+        Some(side)
+    }
+
+    fn climb_to_root(&mut self) {
+        while self.ascend().is_some() {}
+    }
+
+    fn len(&self) -> usize {
+        /*
+            Size of the whole tree, read from the root's counts.
+        */
+        match self.path.first() {
+            Some((root, _)) => 1 + root.left_count + root.right_count,
+            None => TreeNode::subtree_size(&self.slot),
+        }
+    }
+
+    pub(crate) fn rank(&self) -> usize {
+        /*
+            Number of keys before the slot, from the counts along the path.
+        */
+        let inside = self.slot.as_ref().map_or(0, |n| n.left_count);
+        let before: usize = self
+            .path
+            .iter()
+            .filter(|(_, side)| *side == Side::Right)
+            .map(|(n, _)| n.left_count + 1)
+            .sum();
+        inside + before
+    }
+
+    fn seek(&mut self, mut k: usize) -> bool {
+        /*
+            Move the slot to the entry with `k` keys before it, walking down the
+            counts. Returns false, with the slot at the root, if there is none.
+        */
+        self.climb_to_root();
+        if k >= self.len() {
+            return false;
+        }
+        loop {
+            let node = self.slot.as_ref().expect("k is in range");
+            match k.cmp(&node.left_count) {
+                Ordering::Less => self.descend(Side::Left),
+                Ordering::Equal => return true,
+                Ordering::Greater => {
+                    k -= node.left_count + 1;
+                    self.descend(Side::Right);
+                }
+            }
+        }
+    }
+
+    fn step(&mut self, side: Side) -> bool {
+        /*
+            Move from the entry in the slot to its in-order neighbour on `side`.
+            Returns false, with the slot at the root, if there is none.
+        */
+        let node = self.slot.as_ref().expect("step from an entry");
+        if side.of(node).is_some() {
+            self.descend(side);
+            self.descend_to_end(side.other());
+            return true;
+        }
+        loop {
+            match self.ascend() {
+                Some(came_from) if came_from == side.other() => return true,
+                Some(_) => {}
+                None => return false,
+            }
+        }
+    }
+
+    fn descend_to_end(&mut self, side: Side) {
+        /*
+            Follow `side` children down from the slot to the last one.
+        */
+        while self
+            .slot
+            .as_ref()
+            .is_some_and(|node| side.of(node).is_some())
+        {
+            self.descend(side);
+        }
+    }
+}
+
+impl<K: Ord, V, A: Augment<K, V>> Zipper<'_, K, V, A> {
+    pub(crate) fn find(&mut self, key: &K) -> bool {
+        /*
+            Search from the root. Returns true with the slot on `key`'s node, or
+            false with the slot on the empty child where `key` would be attached.
+        */
+        self.climb_to_root();
+        while let Some(node) = self.slot.as_ref() {
+            match key.cmp(&node.key) {
+                Ordering::Less => self.descend(Side::Left),
+                Ordering::Equal => return true,
+                Ordering::Greater => self.descend(Side::Right),
+            }
+        }
+        false
+    }
+
+    pub(crate) fn insert(&mut self, key: K, value: V) {
+        /*
+            Attach a new leaf in the empty slot `find` stopped at, with the counts,
+            summaries and balance of its ancestors fixed as `insert` would. Leaves
+            the slot at the root.
+        */
+        debug_assert!(self.slot.is_none(), "insert into an occupied slot");
+        let tree = self.tree.as_deref_mut().expect("zipper is open");
+        let before = self
+            .path
+            .first()
+            .map_or(std::ptr::null(), |(r, _)| &**r as *const _);
+        for (node, side) in &mut self.path {
+            match side {
+                Side::Left => node.left_count += 1,
+                Side::Right => node.right_count += 1,
+            }
+        }
+        let alpha = tree.alpha;
+        let scapegoat = self
+            .path
+            .iter()
+            .position(|(n, _)| BalancedTree::<K, V, A>::is_unbalanced(alpha, n));
+
+        let mut op = Op::new(&mut tree.observer);
+        op.counts.visits += self.path.len() as u64;
+        let leaf = self.slot.insert(BalancedTree::<K, V, A>::leaf(key, value));
+        op.emit(crate::observer::TreeEvent::Inserted { key: &leaf.key });
+        Self::rejoin(&mut self.path, &mut self.slot, scapegoat, &mut op);
+        let counts = op.counts;
+        tree.root = self.slot.take();
+        tree.finish_update(counts, before, false);
+        self.slot = tree.root.take();
+    }
+
+    pub(crate) fn remove(&mut self) -> (K, V) {
+        /*
+            Delete the entry in the slot, as `remove` would, and return it. Leaves
+            the slot at the root.
+        */
+        let tree = self.tree.as_deref_mut().expect("zipper is open");
+        let root = self.path.first().map(|(r, _)| r).or(self.slot.as_ref());
+        let before = root.map_or(std::ptr::null(), |r| &**r as *const _);
+        let at_root = self.path.is_empty();
+        for (node, side) in &mut self.path {
+            match side {
+                Side::Left => node.left_count -= 1,
+                Side::Right => node.right_count -= 1,
+            }
+        }
+        let alpha = tree.alpha;
+        let scapegoat = self
+            .path
+            .iter()
+            .position(|(n, _)| BalancedTree::<K, V, A>::is_unbalanced(alpha, n));
+
+        let mut op = Op::new(&mut tree.observer);
+        op.counts.visits += self.path.len() as u64;
+        // Below a scapegoat nothing needs checking: its rebuild fixes all of it
+        let removed = BalancedTree::<K, V, A>::delete_in(
+            alpha,
+            &mut self.slot,
+            &mut |_| Ordering::Equal,
+            scapegoat.is_none(),
+            &mut op,
+        )
+        .expect("remove from an entry");
+        Self::rejoin(&mut self.path, &mut self.slot, scapegoat, &mut op);
+        let counts = op.counts;
+        tree.root = self.slot.take();
+        tree.finish_update(counts, before, at_root);
+        self.slot = tree.root.take();
+        removed
+    }
+
+    fn rejoin(
+        path: &mut Path<K, V, A>,
+        slot: &mut Link<K, V, A>,
+        scapegoat: Option<usize>,
+        op: &mut Op<'_, K>,
+    ) {
+        /*
+            Climb back to the root after the slot's subtree changed, refreshing
+            every height and summary on the way and rebuilding the node at depth
+            `scapegoat`.
+        */
+        while let Some((mut parent, side)) = path.pop() {
+            match side {
+                Side::Left => parent.left = slot.take(),
+                Side::Right => parent.right = slot.take(),
+            }
+            BalancedTree::<K, V, A>::refresh(&mut parent);
+            *slot = Some(parent);
+            if scapegoat == Some(path.len()) {
+                BalancedTree::<K, V, A>::rebuild(slot, op);
+            }
+        }
+    }
+}
+
+pub struct Cursor<'a, K, V, A: Augment<K, V> = ()> {
+    /*
+        Mutable cursor over a tree. On the ghost position the zipper rests at
+        the root.
+    */
+    zipper: Zipper<'a, K, V, A>,
+    ghost: bool,
+}
+
+// Finds the node with `k` keys before it, walking down by the counts
+pub(crate) fn node_at<K, V, S>(
+    mut cur: Option<&TreeNode<K, V, S>>,
+    mut k: usize,
+) -> Option<&TreeNode<K, V, S>> {
+    while let Some(node) = cur {
+        match k.cmp(&node.left_count) {
+            Ordering::Less => cur = node.left.as_deref(),
+            Ordering::Equal => return Some(node),
+            Ordering::Greater => {
+                k -= node.left_count + 1;
+                cur = node.right.as_deref();
+            }
+        }
+    }
+    None
+}
+
+impl<K: Ord, V, A: Augment<K, V>> BalancedTree<K, V, A> {
+    pub fn lower_bound_cursor(&mut self, key: &K) -> Cursor<'_, K, V, A> {
+        /*
+            A cursor on the first entry whose key is ≥ `key`, or on the ghost
+            position if there is none. One search, O(log n).
+        */
+        let mut zipper = Zipper::open(self);
+        // Off the tree, the lower bound is the nearest ancestor left behind
+        // to the left
+        let found = zipper.find(key)
+            || loop {
+                match zipper.ascend() {
+                    Some(Side::Left) => break true,
+                    Some(Side::Right) => {}
+                    None => break false,
+                }
+            };
+        Cursor {
+            zipper,
+            ghost: !found,
+        }
+    }
+}
+
+impl<K: Ord, V, A: Augment<K, V>> Cursor<'_, K, V, A> {
+    pub fn key(&self) -> Option<&K> {
+        /*
+            The current key, or None on the ghost position.
+        */
+        self.current().map(|(k, _)| k)
+    }
+
+    pub fn value(&self) -> Option<&V> {
+        /*
+            The current value, or None on the ghost position.
+        */
+        self.current().map(|(_, v)| v)
+    }
+
+    pub fn current(&self) -> Option<(&K, &V)> {
+        /*
+            The current entry, or None on the ghost position.
+        */
+        let node = self.zipper.slot.as_deref().filter(|_| !self.ghost)?;
+        Some((&node.key, &node.value))
+    }
+
+    pub fn move_next(&mut self) {
+        /*
+            Step to the next entry; from the last entry to the ghost position and
+            from the ghost to the first entry.
+        */
+        self.step(Side::Right);
+    }
+
+    pub fn move_prev(&mut self) {
+        /*
+            Step to the previous entry; from the first entry to the ghost position
+            and from the ghost to the last entry.
+        */
+        self.step(Side::Left);
+    }
+
+    fn step(&mut self, side: Side) {
+        if !self.ghost {
+            self.ghost = !self.zipper.step(side);
+        } else if self.zipper.slot.is_some() {
+            self.zipper.descend_to_end(side.other());
+            self.ghost = false;
+        }
+    }
+
+    pub fn remove_current(&mut self) -> Option<(K, V)> {
+        /*
+            Delete the current entry and return it. The cursor moves on to the next
+            entry (or the ghost position if it removed the last one), found again
+            by its rank. Does nothing on the ghost position.
+        */
+        if self.ghost {
+            return None;
+        }
+        let index = self.zipper.rank();
+        let removed = self.zipper.remove();
+        self.ghost = !self.zipper.seek(index);
+        Some(removed)
+    }
+}
+
+impl<K: Ord, V> Cursor<'_, K, V> {
+    pub fn value_mut(&mut self) -> Option<&mut V> {
+        /*
+            The current value for in-place modification, or None on the ghost
+            position. Like `get_mut`, only trees without an augmentation offer it.
+        */
+        let node = self.zipper.slot.as_deref_mut().filter(|_| !self.ghost)?;
+        Some(&mut node.value)
+    }
+}
+
+impl<K: Ord, V> BalancedTree<K, V> {
+    pub(crate) fn value_at_mut(&mut self, mut k: usize) -> Option<&mut V> {
+        /*
+            The value of the entry with `k` keys before it, found by the counts.
+        */
+        let mut cur = self.root.as_deref_mut();
+        while let Some(node) = cur {
+            match k.cmp(&node.left_count) {
+                Ordering::Less => cur = node.left.as_deref_mut(),
+                Ordering::Equal => return Some(&mut node.value),
+                Ordering::Greater => {
+                    k -= node.left_count + 1;
+                    cur = node.right.as_deref_mut();
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{assert_valid, xorshift};
+
+    #[test]
+    fn test_walk_and_edit_neighbours() {
+        let mut tree: BalancedTree<i32, i32> = (0..10).map(|k| (k * 10, k)).collect();
+        let mut cursor = tree.lower_bound_cursor(&25);
+        assert_eq!(cursor.current(), Some((&30, &3)));
+        cursor.move_prev();
+        assert_eq!(cursor.key(), Some(&20));
+        *cursor.value_mut().unwrap() += 100;
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some((40, 4)));
+        // The cursor now sits on the entry after the removed one
+        assert_eq!(cursor.key(), Some(&50));
+        // Dropping the cursor puts the tree back together
+        drop(cursor);
+        assert_valid(&tree);
+        assert_eq!(tree.get(&20), Some(&102));
+        assert!(!tree.contains_key(&40));
+
+        // Exact match, and nothing at or above the key
+        assert_eq!(tree.lower_bound_cursor(&50).key(), Some(&50));
+        let mut cursor = tree.lower_bound_cursor(&1000);
+        assert_eq!(cursor.key(), None);
+        assert_eq!(cursor.remove_current(), None);
+        cursor.move_next();
+        assert_eq!(cursor.key(), Some(&0));
+        cursor.move_prev();
+        assert_eq!(cursor.key(), None);
+        cursor.move_prev();
+        assert_eq!(cursor.key(), Some(&90));
+    }
+
+    #[test]
+    fn test_matches_sorted_vec() {
+        let mut next = xorshift(0x0c05_50a5_1234_abcd);
+        for strict in [false, true] {
+            let mut tree = if strict {
+                BalancedTree::strict()
+            } else {
+                BalancedTree::new()
+            };
+            let mut model: Vec<u32> = (0..500).map(|k| k * 2).collect();
+            tree.extend(model.iter().map(|&k| (k, ())));
+            for _ in 0..100 {
+                let start = (next() % 1020) as u32;
+                let mut cursor = tree.lower_bound_cursor(&start);
+                let mut i = model.partition_point(|&k| k < start);
+                for _ in 0..next() % 8 {
+                    match next() % 3 {
+                        0 => {
+                            cursor.move_next();
+                            i = if i >= model.len() { 0 } else { i + 1 };
+                        }
+                        1 => {
+                            cursor.move_prev();
+                            i = if i == 0 { model.len() } else { i - 1 };
+                        }
+                        _ => {
+                            let expected = (i < model.len()).then(|| (model.remove(i), ()));
+                            assert_eq!(cursor.remove_current(), expected);
+                        }
+                    }
+                    assert_eq!(cursor.key(), model.get(i));
+                }
+                drop(cursor);
+                assert_valid(&tree);
+                assert!(tree.keys().eq(model.iter()));
+            }
+        }
+    }
+}
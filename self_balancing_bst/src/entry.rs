@@ -0,0 +1,283 @@
+/*
+    Entry
+        look a key up once, then insert or edit it in place
+
+    `entry(key)` reports whether `key` is present and returns a handle that can
+    insert a value or edit the existing one without the caller searching again:
+
+        *counts.entry(word).or_insert(0) += 1;
+        tree.entry(k).and_modify(|v| *v *= 2).or_insert(1);
+
+    The handle holds the tree taken apart along the search path (see `cursor`),
+    so the key is compared only on the way down. An occupied entry reads and
+    edits its node directly, and a vacant one attaches the new leaf in the empty
+    slot where the search ended. Only the `&'a mut V` handed out by `into_mut`
+    and `insert` needs one more walk, down the counts to the entry's rank, once
+    the tree is back together.
+
+    Only trees without an augmentation have entries, since the handle gives out
+    `&mut V` and a summary could not be refreshed after the value changes.
+*/
+
+use crate::{BalancedTree, cursor::Zipper};
+
+pub enum Entry<'a, K, V> {
+    /*
+        A view into a single key of a tree, present or not.
+    */
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+pub struct OccupiedEntry<'a, K, V> {
+    // Open on the key's node
+    zipper: Zipper<'a, K, V, ()>,
+}
+
+pub struct VacantEntry<'a, K, V> {
+    // Open on the empty slot where `key` belongs
+    zipper: Zipper<'a, K, V, ()>,
+    key: K,
+}
+
+impl<K: Ord, V> BalancedTree<K, V> {
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        /*
+            The entry for `key`, for in-place insertion or modification.
+        */
+        let mut zipper = Zipper::open(self);
+        if zipper.find(&key) {
+            Entry::Occupied(OccupiedEntry { zipper })
+        } else {
+            Entry::Vacant(VacantEntry { zipper, key })
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Entry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        /*
+            The key this entry was made for.
+        */
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        /*
+            The value under the key, inserting `default` first if there is none.
+        */
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        /*
+            The value under the key, inserting `default()` first if there is none.
+            `default` is only called for a vacant entry.
+        */
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        /*
+            The value under the key, inserting `V::default()` first if there is none.
+        */
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        /*
+            Apply `f` to the value if the key is present, then hand the entry back
+            so it can be chained with `or_insert`.
+        */
+        match self {
+            Entry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Entry::Occupied(entry)
+            }
+            vacant => vacant,
+        }
+    }
+}
+
+impl<'a, K: Ord, V> OccupiedEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.zipper.slot.as_ref().expect("occupied entry").key
+    }
+
+    pub fn get(&self) -> &V {
+        &self.zipper.slot.as_ref().expect("occupied entry").value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.zipper.slot.as_mut().expect("occupied entry").value
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        /*
+            The value, borrowed for as long as the tree was. Found again by its
+            rank once the tree is put back together.
+        */
+        let index = self.zipper.rank();
+        self.zipper
+            .into_tree()
+            .value_at_mut(index)
+            .expect("occupied entry")
+    }
+
+    pub fn insert(&mut self, value: V) -> V {
+        /*
+            Replace the value and return the old one.
+        */
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove(self) -> V {
+        /*
+            Delete the entry from the tree and return its value.
+        */
+        let mut zipper = self.zipper;
+        zipper.remove().1
+    }
+}
+
+impl<'a, K: Ord, V> VacantEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    pub fn insert(self, value: V) -> &'a mut V {
+        /*
+            Insert `value` under the key and return it for further editing. The
+            new entry is found again by its rank, which the insertion cannot change.
+        */
+        let Self { mut zipper, key } = self;
+        let index = zipper.rank();
+        zipper.insert(key, value);
+        zipper
+            .into_tree()
+            .value_at_mut(index)
+            .expect("just inserted")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        observer::RecordingObserver,
+        tests::{assert_valid, xorshift},
+    };
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_word_counts() {
+        let text = "the quick brown fox jumps over the lazy dog the end";
+        let mut counts = BalancedTree::new();
+        let mut model = BTreeMap::new();
+        for word in text.split(' ') {
+            *counts.entry(word).or_insert(0) += 1;
+            *model.entry(word).or_insert(0) += 1;
+            assert_valid(&counts);
+        }
+        assert!(counts.iter().eq(model.iter()));
+        assert_eq!(counts.get(&"the"), Some(&3));
+    }
+
+    #[test]
+    fn test_entry_variants() {
+        let mut tree: BalancedTree<u32, Vec<u32>> = BalancedTree::new();
+        tree.entry(5).or_default().push(1);
+        tree.entry(5)
+            .and_modify(|v| v.push(2))
+            .or_insert_with(|| unreachable!());
+        tree.entry(3)
+            .and_modify(|_| unreachable!())
+            .or_insert(vec![9]);
+        assert_eq!(tree.get(&5), Some(&vec![1, 2]));
+        assert_eq!(tree.get(&3), Some(&vec![9]));
+        assert_valid(&tree);
+
+        match tree.entry(5) {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(entry.key(), &5);
+                assert_eq!(entry.get(), &vec![1, 2]);
+                assert_eq!(entry.insert(vec![7]), vec![1, 2]);
+                assert_eq!(entry.remove(), vec![7]);
+            }
+            Entry::Vacant(_) => panic!("5 is present"),
+        }
+        assert!(!tree.contains_key(&5));
+        match tree.entry(4) {
+            Entry::Vacant(entry) => {
+                assert_eq!(entry.key(), &4);
+                entry.insert(vec![4]).push(44);
+            }
+            Entry::Occupied(_) => panic!("4 is absent"),
+        }
+        assert_eq!(tree.get(&4), Some(&vec![4, 44]));
+        assert_valid(&tree);
+
+        // Vacant inserts land on the right node whatever the rank
+        for key in (0..200).rev() {
+            *tree.entry(key * 10 + 1).or_insert(vec![]) = vec![key];
+            assert_valid(&tree);
+        }
+        assert!((0..200).all(|key| tree.get(&(key * 10 + 1)) == Some(&vec![key])));
+    }
+
+    #[test]
+    fn test_updates_match_insert_and_remove() {
+        let mut next = xorshift(0x3e7a_11ed_f00d_5eed);
+        for strict in [true, false] {
+            let fresh = || {
+                if strict {
+                    BalancedTree::strict()
+                } else {
+                    BalancedTree::new()
+                }
+            };
+            let (by_entry_log, by_key_log) = (RecordingObserver::new(), RecordingObserver::new());
+            let (mut by_entry, mut by_key) = (fresh(), fresh());
+            by_entry.set_observer(by_entry_log.clone());
+            by_key.set_observer(by_key_log.clone());
+            by_entry.enable_stats();
+            by_key.enable_stats();
+            for _ in 0..1500 {
+                let key = (next() % 200) as u32;
+                match (next() % 3, by_entry.entry(key)) {
+                    (0, Entry::Occupied(entry)) => {
+                        assert_eq!(Some(entry.remove()), by_key.remove(&key));
+                    }
+                    // An entry dropped unused leaves the tree as it was
+                    (1, _) => {}
+                    (_, entry) => {
+                        *entry.or_insert(0) += 1;
+                        // In-place edits are not updates, as with get_mut
+                        match by_key.get_mut(&key) {
+                            Some(value) => *value += 1,
+                            None => drop(by_key.insert(key, 1)),
+                        }
+                    }
+                }
+                assert_valid(&by_entry);
+                assert_eq!(by_entry_log.take(), by_key_log.take());
+                assert!(by_entry.iter().eq(by_key.iter()));
+            }
+            assert_eq!(by_entry.height(), by_key.height());
+            assert_eq!(by_entry.stats(), by_key.stats());
+        }
+    }
+}
@@ -0,0 +1,320 @@
+/*
+    Interval Tree
+        closed intervals [lo, hi] in a BalancedTree, augmented with the max endpoint
+
+    Intervals are keyed by `lo` (ties broken by `hi`), and every node keeps the
+    largest `hi` in its subtree through the `MaxEnd` augmentation, which the tree
+    refreshes on every insert, delete and rebuild. That one number is enough to
+    prune whole subtrees during a search:
+
+        if a subtree's largest `hi` is below the query's `lo`, nothing in it can
+        overlap the query; if a node's `lo` is above the query's `hi`, neither can
+        anything in its right subtree
+
+    find_overlap   any one overlapping interval          O(log n)
+    overlapping    all k overlapping intervals           O(k log n)
+    stabbing       all intervals containing a point      O(k log n)
+*/
+
+use crate::{BalancedTree, TreeNode, augment::Augment};
+
+/// Interval
+///
+/// The closed interval [lo, hi]. Ordered by `lo`, then by `hi`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval<T> {
+    pub lo: T,
+    pub hi: T,
+}
+
+impl<T: Ord> Interval<T> {
+    /// new()
+    ///
+    /// Panics if `lo > hi`.
+    ///
+    /// Returns the interval [lo, hi].
+    ///
+    pub fn new(lo: T, hi: T) -> Self {
+        assert!(lo <= hi, "Interval::new: lo must not be above hi");
+        Interval { lo, hi }
+    }
+
+    /// overlaps()
+    ///
+    /// Returns true if the two closed intervals share at least one point.
+    ///
+    pub fn overlaps(&self, other: &Interval<T>) -> bool {
+        self.lo <= other.hi && other.lo <= self.hi
+    }
+
+    /// contains()
+    ///
+    /// Returns true if `point` lies in [lo, hi].
+    ///
+    pub fn contains(&self, point: &T) -> bool {
+        self.lo <= *point && *point <= self.hi
+    }
+}
+
+// Largest `hi` in a subtree
+struct MaxEnd;
+
+impl<T: Ord + Clone, V> Augment<Interval<T>, V> for MaxEnd {
+    type Summary = Option<T>;
+
+    fn empty() -> Option<T> {
+        None
+    }
+
+    fn single(interval: &Interval<T>, _: &V) -> Option<T> {
+        Some(interval.hi.clone())
+    }
+
+    fn combine(left: &Option<T>, right: &Option<T>) -> Option<T> {
+        left.clone().max(right.clone())
+    }
+}
+
+type Node<T, V> = TreeNode<Interval<T>, V, Option<T>>;
+
+pub struct IntervalTree<T: Ord + Clone, V> {
+    /*
+        A map from closed intervals to values. Inserting an interval that is
+        already stored replaces its value; intervals that merely overlap are all
+        kept.
+    */
+    tree: BalancedTree<Interval<T>, V, MaxEnd>,
+}
+
+impl<T: Ord + Clone, V> Default for IntervalTree<T, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone, V> IntervalTree<T, V> {
+    pub fn new() -> Self {
+        /*
+            Create a new, empty interval tree.
+        */
+        Self {
+            tree: BalancedTree::with_augment(MaxEnd),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        /*
+            Number of intervals stored.
+        */
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        /*
+            Whether no interval is stored.
+        */
+        self.tree.is_empty()
+    }
+
+    pub fn insert(&mut self, interval: Interval<T>, value: V) -> Option<V> {
+        /*
+            Store `interval`, or replace the value of an identical interval and
+            return the old value.
+        */
+        self.tree.insert(interval, value)
+    }
+
+    pub fn remove(&mut self, interval: &Interval<T>) -> Option<V> {
+        /*
+            Delete exactly this interval and return its value.
+        */
+        self.tree.remove(interval)
+    }
+
+    pub fn get(&self, interval: &Interval<T>) -> Option<&V> {
+        /*
+            Look up the value stored for exactly this interval.
+        */
+        self.tree.get(interval)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Interval<T>, &V)> {
+        /*
+            Iterate over all intervals ordered by `lo`, then `hi`.
+        */
+        self.tree.iter()
+    }
+
+    pub fn max_end(&self) -> Option<&T> {
+        /*
+            The largest `hi` of all stored intervals, read from the root in O(1).
+        */
+        self.tree.root.as_ref().and_then(|r| r.summary.as_ref())
+    }
+
+    pub fn find_overlap(&self, query: &Interval<T>) -> Option<(&Interval<T>, &V)> {
+        /*
+            Some stored interval that overlaps `query`, in O(log n).
+
+            Q: Why is it safe to go right whenever the left subtree's max endpoint
+               is below query.lo?
+
+            A: Then no interval on the left ends at or after query.lo, so none of
+               them overlaps. If instead it does reach query.lo and still nothing
+               on the left overlaps, every left interval starts after query.hi, and
+               so does every interval on the right.
+        */
+        let mut cur = self.tree.root.as_deref();
+        while let Some(node) = cur {
+            if node.key.overlaps(query) {
+                return Some((&node.key, &node.value));
+            }
+            cur = match node.left.as_deref() {
+                Some(left) if left.summary.as_ref().is_some_and(|max| *max >= query.lo) => {
+                    Some(left)
+                }
+                _ => node.right.as_deref(),
+            };
+        }
+        None
+    }
+
+    pub fn overlapping(&self, query: &Interval<T>) -> Vec<(&Interval<T>, &V)> {
+        /*
+            All stored intervals that overlap `query`, ordered by `lo`. Visits
+            O(k log n) nodes for k results.
+        */
+        fn collect<'a, T: Ord, V>(
+            node: Option<&'a Node<T, V>>,
+            query: &Interval<T>,
+            out: &mut Vec<(&'a Interval<T>, &'a V)>,
+        ) {
+            let Some(n) = node else {
+                return;
+            };
+            // Nothing below ends late enough to reach the query
+            if n.summary.as_ref().is_none_or(|max| *max < query.lo) {
+                return;
+            }
+            collect(n.left.as_deref(), query, out);
+            // This interval and everything to its right start after the query
+            if n.key.lo > query.hi {
+                return;
+            }
+            if n.key.overlaps(query) {
+                out.push((&n.key, &n.value));
+            }
+            collect(n.right.as_deref(), query, out);
+        }
+
+        let mut out = Vec::new();
+        collect(self.tree.root.as_deref(), query, &mut out);
+        out
+    }
+
+    pub fn stabbing(&self, point: &T) -> Vec<(&Interval<T>, &V)> {
+        /*
+            All stored intervals that contain `point`, ordered by `lo`.
+        */
+        self.overlapping(&Interval {
+            lo: point.clone(),
+            hi: point.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{assert_valid, xorshift};
+
+    fn brute<'a>(
+        all: &'a [(Interval<u32>, usize)],
+        query: &Interval<u32>,
+    ) -> Vec<(&'a Interval<u32>, &'a usize)> {
+        all.iter()
+            .filter(|(i, _)| i.overlaps(query))
+            .map(|(i, v)| (i, v))
+            .collect()
+    }
+
+    #[test]
+    fn test_booking_conflicts() {
+        let mut bookings = IntervalTree::new();
+        for (lo, hi, who) in [
+            (9, 10, "ana"),
+            (13, 15, "bo"),
+            (10, 12, "cy"),
+            (16, 17, "di"),
+        ] {
+            let slot = Interval::new(lo, hi);
+            // Closed intervals: 9–10 and 10–12 share the point 10
+            let conflict = bookings.find_overlap(&slot).map(|(_, who)| *who);
+            assert_eq!(conflict, if who == "cy" { Some("ana") } else { None });
+            bookings.insert(slot, who);
+            assert_valid(&bookings.tree);
+        }
+        assert_eq!(bookings.max_end(), Some(&17));
+        fn who(found: Vec<(&Interval<i32>, &&'static str)>) -> Vec<&'static str> {
+            found.into_iter().map(|(_, who)| *who).collect()
+        }
+        assert_eq!(who(bookings.stabbing(&10)), ["ana", "cy"]);
+        assert_eq!(
+            who(bookings.overlapping(&Interval::new(11, 16))),
+            ["cy", "bo", "di"]
+        );
+        assert!(bookings.overlapping(&Interval::new(18, 20)).is_empty());
+
+        assert_eq!(bookings.remove(&Interval::new(16, 17)), Some("di"));
+        assert_eq!(bookings.remove(&Interval::new(16, 17)), None);
+        assert_valid(&bookings.tree);
+        assert_eq!(bookings.max_end(), Some(&15));
+        assert_eq!(bookings.find_overlap(&Interval::new(16, 20)), None);
+        assert_eq!(bookings.get(&Interval::new(13, 15)), Some(&"bo"));
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let mut next = xorshift(0x1a7e_4a15_0bad_f00d);
+        let mut tree = IntervalTree::new();
+        let mut all: Vec<(Interval<u32>, usize)> = Vec::new();
+        for step in 0..3000 {
+            let lo = (next() % 1000) as u32;
+            let interval = Interval::new(lo, lo + (next() % 50) as u32);
+            if next().is_multiple_of(3) && !all.is_empty() {
+                let (gone, value) = all.swap_remove((next() % all.len() as u64) as usize);
+                assert_eq!(tree.remove(&gone), Some(value));
+            } else if tree.insert(interval, step).is_some() {
+                all.iter_mut().find(|(i, _)| *i == interval).unwrap().1 = step;
+            } else {
+                all.push((interval, step));
+            }
+            assert_valid(&tree.tree);
+            assert_eq!(tree.len(), all.len());
+
+            all.sort();
+            let lo = (next() % 1100) as u32;
+            let query = Interval::new(lo, lo + (next() % 30) as u32);
+            let expected = brute(&all, &query);
+            assert_eq!(tree.overlapping(&query), expected);
+            assert_eq!(tree.find_overlap(&query).is_some(), !expected.is_empty());
+            if let Some((found, _)) = tree.find_overlap(&query) {
+                assert!(found.overlaps(&query));
+            }
+            let point = (next() % 1100) as u32;
+            let stabbed = tree.stabbing(&point);
+            assert!(stabbed.iter().all(|(i, _)| i.contains(&point)));
+            assert_eq!(
+                stabbed.len(),
+                all.iter().filter(|(i, _)| i.contains(&point)).count()
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "lo must not be above hi")]
+    fn test_rejects_inverted_interval() {
+        Interval::new(5, 4);
+    }
+}
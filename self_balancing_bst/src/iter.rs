@@ -0,0 +1,458 @@
+/*
+    Iterators
+        in-order traversal of a BalancedTree without copying it
+
+    Borrowing iterators keep two stacks of node references, one for each end. The
+    cached subtree counts give the exact number of keys left, so both ends stop
+    when they meet and `range` can size itself with two rank queries. The owning
+    iterator takes the tree apart the same way, holding the unlinked nodes
+    themselves.
+*/
+
+use std::{
+    collections::VecDeque,
+    ops::{Bound, RangeBounds},
+};
+
+use crate::{BalancedTree, TreeNode, augment::Augment};
+
+pub struct Iter<'a, K, V, S = ()> {
+    /*
+        Borrowing iterator over `(&key, &value)` in key order, from either end.
+
+        `front` holds the nodes whose key (and right subtree) are still to be
+        visited from the low end, `back` the same from the high end.
+    */
+    front: Vec<&'a TreeNode<K, V, S>>,
+    back: Vec<&'a TreeNode<K, V, S>>,
+    remaining: usize,
+}
+
+impl<'a, K: Ord, V, S> Iter<'a, K, V, S> {
+    fn new(
+        root: &'a Option<Box<TreeNode<K, V, S>>>,
+        low: Bound<&K>,
+        high: Bound<&K>,
+        remaining: usize,
+    ) -> Self {
+        /*
+            Seed both stacks with the search paths of the two bounds. Every node that
+            satisfies a bound is pushed before descending towards the bound, so the
+            top of each stack is the first key inside the range at that end.
+        */
+        let mut front = Vec::new();
+        let mut cur = root.as_deref();
+        while let Some(node) = cur {
+            let inside = match low {
+                Bound::Included(x) => node.key >= *x,
+                Bound::Excluded(x) => node.key > *x,
+                Bound::Unbounded => true,
+            };
+            if inside {
+                front.push(node);
+                cur = node.left.as_deref();
+            } else {
+                cur = node.right.as_deref();
+            }
+        }
+
+        let mut back = Vec::new();
+        let mut cur = root.as_deref();
+        while let Some(node) = cur {
+            let inside = match high {
+                Bound::Included(x) => node.key <= *x,
+                Bound::Excluded(x) => node.key < *x,
+                Bound::Unbounded => true,
+            };
+            if inside {
+                back.push(node);
+                cur = node.right.as_deref();
+            } else {
+                cur = node.left.as_deref();
+            }
+        }
+
+        Self {
+            front,
+            back,
+            remaining,
+        }
+    }
+}
+
+impl<'a, K, V, S> Iterator for Iter<'a, K, V, S> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let node = self.front.pop()?;
+        let mut cur = node.right.as_deref();
+        while let Some(n) = cur {
+            self.front.push(n);
+            cur = n.left.as_deref();
+        }
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V, S> DoubleEndedIterator for Iter<'_, K, V, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let node = self.back.pop()?;
+        let mut cur = node.left.as_deref();
+        while let Some(n) = cur {
+            self.back.push(n);
+            cur = n.right.as_deref();
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<K, V, S> ExactSizeIterator for Iter<'_, K, V, S> {}
+
+pub struct Keys<'a, K, V, S = ()>(Iter<'a, K, V, S>);
+
+impl<'a, K, V, S> Iterator for Keys<'a, K, V, S> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, _)| k)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V, S> DoubleEndedIterator for Keys<'_, K, V, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(k, _)| k)
+    }
+}
+
+impl<K, V, S> ExactSizeIterator for Keys<'_, K, V, S> {}
+
+pub struct Values<'a, K, V, S = ()>(Iter<'a, K, V, S>);
+
+impl<'a, K, V, S> Iterator for Values<'a, K, V, S> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, v)| v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V, S> DoubleEndedIterator for Values<'_, K, V, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(_, v)| v)
+    }
+}
+
+impl<K, V, S> ExactSizeIterator for Values<'_, K, V, S> {}
+
+pub struct IntoIter<K, V, S = ()> {
+    /*
+        Owning iterator over `(key, value)` in key order, from either end. Nodes
+        are unlinked only as the iteration reaches them, so taking a few entries
+        costs O(height) rather than O(n), and entries are moved out, never cloned.
+
+        `front` holds nodes with their left child taken off, each still owing its
+        own entry and its right subtree, smallest on top. `back` holds nodes with
+        their right child taken off, owing their left subtree and their entry,
+        largest on top. When one end runs dry it takes over the bottom node of
+        the other, which holds the entries closest to it.
+    */
+    front: VecDeque<Box<TreeNode<K, V, S>>>,
+    back: VecDeque<Box<TreeNode<K, V, S>>>,
+    remaining: usize,
+}
+
+impl<K, V, S> IntoIter<K, V, S> {
+    fn push_left_spine(&mut self, mut link: Option<Box<TreeNode<K, V, S>>>) {
+        while let Some(mut node) = link {
+            link = node.left.take();
+            self.front.push_back(node);
+        }
+    }
+
+    fn push_right_spine(&mut self, mut link: Option<Box<TreeNode<K, V, S>>>) {
+        while let Some(mut node) = link {
+            link = node.right.take();
+            self.back.push_back(node);
+        }
+    }
+}
+
+impl<K, V, S> Iterator for IntoIter<K, V, S> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(mut node) = self.front.pop_back() {
+                let right = node.right.take();
+                self.push_left_spine(right);
+                self.remaining -= 1;
+                return Some((node.key, node.value));
+            }
+            // The lowest entries left are the left subtree and entry of the
+            // bottom node at the back
+            let mut node = self.back.pop_front()?;
+            let left = node.left.take();
+            self.front.push_back(node);
+            self.push_left_spine(left);
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V, S> DoubleEndedIterator for IntoIter<K, V, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(mut node) = self.back.pop_back() {
+                let left = node.left.take();
+                self.push_right_spine(left);
+                self.remaining -= 1;
+                return Some((node.key, node.value));
+            }
+            let mut node = self.front.pop_front()?;
+            let right = node.right.take();
+            self.back.push_back(node);
+            self.push_right_spine(right);
+        }
+    }
+}
+
+impl<K, V, S> ExactSizeIterator for IntoIter<K, V, S> {}
+
+impl<K: Ord, V, A: Augment<K, V>> BalancedTree<K, V, A> {
+    pub fn iter(&self) -> Iter<'_, K, V, A::Summary> {
+        /*
+            Iterate over all entries in key order.
+        */
+        self.range(..)
+    }
+
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Iter<'_, K, V, A::Summary> {
+        /*
+            Iterate over the entries whose keys fall inside `range`, e.g. `a..b`,
+            `a..=b`, `..b` or `a..`. An empty or inverted range yields nothing.
+
+            Setting up costs O(height); each step is amortized O(1).
+        */
+        let low = range.start_bound();
+        let high = range.end_bound();
+        let below = match low {
+            Bound::Included(x) => self.rank(x),
+            Bound::Excluded(x) => self.rank_inclusive(x),
+            Bound::Unbounded => 0,
+        };
+        let through = match high {
+            Bound::Included(x) => self.rank_inclusive(x),
+            Bound::Excluded(x) => self.rank(x),
+            Bound::Unbounded => self.len(),
+        };
+        Iter::new(&self.root, low, high, through.saturating_sub(below))
+    }
+
+    pub fn keys(&self) -> Keys<'_, K, V, A::Summary> {
+        /*
+            Iterate over the keys in order.
+        */
+        Keys(self.iter())
+    }
+
+    pub fn values(&self) -> Values<'_, K, V, A::Summary> {
+        /*
+            Iterate over the values in key order.
+        */
+        Values(self.iter())
+    }
+}
+
+impl<'a, K: Ord, V, A: Augment<K, V>> IntoIterator for &'a BalancedTree<K, V, A> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V, A::Summary>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V, A: Augment<K, V>> IntoIterator for BalancedTree<K, V, A> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, A::Summary>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        /*
+            Setting up costs O(height); each step is amortized O(1).
+        */
+        let remaining = TreeNode::subtree_size(&self.root);
+        let mut iter = IntoIter {
+            front: VecDeque::new(),
+            back: VecDeque::new(),
+            remaining,
+        };
+        iter.push_left_spine(self.root);
+        iter
+    }
+}
+
+impl<K: Ord, V, A: Augment<K, V>> FromIterator<(K, V)> for BalancedTree<K, V, A> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self::build_unsorted(iter.into_iter().collect(), (2, 3))
+    }
+}
+
+impl<K: Ord, V, A: Augment<K, V>> Extend<(K, V)> for BalancedTree<K, V, A> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{assert_valid, xorshift};
+
+    fn tree(keys: impl IntoIterator<Item = i32>) -> BalancedTree<i32, i32> {
+        let tree = keys.into_iter().map(|k| (k, k * 10)).collect();
+        assert_valid(&tree);
+        tree
+    }
+
+    #[test]
+    fn test_iter_both_ends() {
+        let t = tree([5, 1, 9, 3, 7, 2, 8]);
+        let keys: Vec<i32> = t.keys().copied().collect();
+        assert_eq!(keys, vec![1, 2, 3, 5, 7, 8, 9]);
+        let rev: Vec<i32> = t.keys().rev().copied().collect();
+        assert_eq!(rev, vec![9, 8, 7, 5, 3, 2, 1]);
+
+        // Alternating ends meet in the middle without repeating a key
+        let mut it = t.iter();
+        assert_eq!(it.len(), 7);
+        assert_eq!(it.next(), Some((&1, &10)));
+        assert_eq!(it.next_back(), Some((&9, &90)));
+        assert_eq!(it.next(), Some((&2, &20)));
+        assert_eq!(it.len(), 4);
+        let middle: Vec<i32> = it.map(|(k, _)| *k).collect();
+        assert_eq!(middle, vec![3, 5, 7, 8]);
+        assert_eq!(t.values().sum::<i32>(), 350);
+    }
+
+    #[test]
+    fn test_range_bounds() {
+        let t = tree((0..20).map(|k| k * 2));
+        let keys = |it: Iter<'_, i32, i32>| it.map(|(k, _)| *k).collect::<Vec<i32>>();
+        assert_eq!(keys(t.range(4..10)), vec![4, 6, 8]);
+        assert_eq!(keys(t.range(4..=10)), vec![4, 6, 8, 10]);
+        assert_eq!(keys(t.range(5..11)), vec![6, 8, 10]);
+        assert_eq!(keys(t.range(..3)), vec![0, 2]);
+        assert_eq!(keys(t.range(35..)), vec![36, 38]);
+        assert_eq!(
+            keys(t.range((Bound::Excluded(4), Bound::Excluded(10)))),
+            vec![6, 8]
+        );
+        assert_eq!(
+            keys(t.range((Bound::Included(10), Bound::Excluded(4)))),
+            vec![]
+        );
+        assert_eq!(keys(t.range(100..)), vec![]);
+        assert_eq!(t.range(..).next_back(), Some((&38, &380)));
+        assert_eq!(t.range(3..=33).len(), 15);
+    }
+
+    #[test]
+    fn test_range_matches_btreemap() {
+        use std::collections::BTreeMap;
+        let mut next = xorshift(0xdead_beef_cafe_f00d);
+        let mut t = BalancedTree::new();
+        let mut model = BTreeMap::new();
+        for _ in 0..300 {
+            let k = (next() % 500) as i32;
+            t.insert(k, ());
+            assert_valid(&t);
+            model.insert(k, ());
+        }
+        for _ in 0..200 {
+            let a = (next() % 520) as i32 - 10;
+            let b = a + (next() % 120) as i32;
+            let got: Vec<&i32> = t.range(a..b).map(|(k, _)| k).collect();
+            let want: Vec<&i32> = model.range(a..b).map(|(k, _)| k).collect();
+            assert_eq!(got, want);
+            let got: Vec<&i32> = t.range(a..=b).rev().map(|(k, _)| k).collect();
+            let want: Vec<&i32> = model.range(a..=b).rev().map(|(k, _)| k).collect();
+            assert_eq!(got, want);
+        }
+    }
+
+    #[test]
+    fn test_owned_iter_from_both_ends() {
+        let mut next = xorshift(0x1d1e_5a7e_0123_4567);
+        for n in 0..60 {
+            let mut model: std::collections::VecDeque<(u64, String)> =
+                (0..n).map(|k| (k, k.to_string())).collect();
+            let t: BalancedTree<u64, String> = model.iter().cloned().collect();
+            let mut owned = t.into_iter();
+            loop {
+                assert_eq!(owned.len(), model.len());
+                let (got, want) = match next() % 3 {
+                    0 => (owned.next_back(), model.pop_back()),
+                    // Stop early now and then: the rest is dropped unvisited
+                    1 if next().is_multiple_of(8) => break,
+                    _ => (owned.next(), model.pop_front()),
+                };
+                assert_eq!(got, want);
+                if got.is_none() {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_owned_and_collect() {
+        let mut t = tree([3, 1, 2]);
+        t.extend([(0, 0), (2, 99)]);
+        assert_valid(&t);
+        assert_eq!(t.get(&2), Some(&99));
+
+        let mut sum = 0;
+        for (k, _) in &t {
+            sum += k;
+        }
+        assert_eq!(sum, 6);
+
+        let owned: Vec<(i32, i32)> = t.into_iter().rev().collect();
+        assert_eq!(owned, vec![(3, 30), (2, 99), (1, 10), (0, 0)]);
+
+        let words: BalancedTree<String, usize> = ["b", "a", "c"]
+            .iter()
+            .map(|w| (w.to_string(), w.len()))
+            .collect();
+        assert_valid(&words);
+        assert_eq!(
+            words.keys().cloned().collect::<Vec<_>>(),
+            vec!["a", "b", "c"]
+        );
+    }
+}
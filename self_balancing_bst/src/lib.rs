@@ -0,0 +1,1294 @@
+use std::{
+    cmp::Ordering,
+    fmt::{Display, Error, Formatter},
+    mem, ptr,
+};
+
+use augment::Augment;
+use observer::{Op, TreeEvent, TreeObserver};
+use stats::{OpCounts, TreeStats};
+
+pub mod augment;
+pub mod avl;
+pub mod cursor;
+pub mod entry;
+pub mod interval;
+pub mod iter;
+pub mod multiset;
+pub mod observer;
+pub mod ordered_set;
+pub mod persistent;
+pub mod red_black;
+mod set_ops;
+pub mod splay;
+pub mod stats;
+#[doc(hidden)]
+pub mod support;
+pub mod treap;
+pub mod validate;
+
+pub struct TreeNode<K, V, S = ()> {
+    /*
+        A single node in the self-balancing BST.
+
+        Stores the `key` and its `value`, cached sizes of left/right subtrees
+        (`left_count`/`right_count`), the `height` of its subtree, the `summary`
+        of its subtree under the tree's `Augment`, and child pointers. Counts,
+        heights and summaries are updated along the search path by every insert
+        and delete.
+    */
+    pub key: K,
+    pub value: V,
+    pub left_count: usize,
+    pub right_count: usize,
+    pub height: usize,
+    pub summary: S,
+    pub left: Option<Box<TreeNode<K, V, S>>>,
+    pub right: Option<Box<TreeNode<K, V, S>>>,
+}
+
+impl<K: Display, V, S> Display for TreeNode<K, V, S> {
+    /*
+       In rust we must implement the Display trait to be able to print a defined struct.
+
+       Will print the tree node in a readable format.
+
+       This allows us to print the tree node in a readable format with simply calling println!("{}", node);
+    */
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            f,
+            "TreeNode {{ key: {}, left_count: {}, right_count: {}, left: {}, right: {}}}",
+            self.key,
+            self.left_count,
+            self.right_count,
+            self.left
+                .as_ref()
+                .map(|n| n.to_string())
+                .unwrap_or_else(|| "None".to_string()),
+            self.right
+                .as_ref()
+                .map(|n| n.to_string())
+                .unwrap_or_else(|| "None".to_string()),
+        )
+    }
+}
+
+impl<K, V, S> TreeNode<K, V, S> {
+    fn new(key: K, value: V, summary: S) -> Self {
+        /*
+           Will create a new tree node with the given key, value and summary.
+        */
+        Self {
+            key,
+            value,
+            left_count: 0,
+            right_count: 0,
+            height: 1,
+            summary,
+            left: None,
+            right: None,
+        }
+    }
+
+    fn subtree_size(node: &Option<Box<TreeNode<K, V, S>>>) -> usize {
+        /*
+            Size of `node`'s subtree, read from its cached counts in O(1).
+        */
+        node.as_ref()
+            .map_or(0, |n| 1 + n.left_count + n.right_count)
+    }
+
+    fn replace_entry(&mut self, key: K, value: V) -> (K, V) {
+        /*
+            Store a new key/value pair in this node and hand back the old one.
+        */
+        (
+            mem::replace(&mut self.key, key),
+            mem::replace(&mut self.value, value),
+        )
+    }
+}
+
+impl<K: Ord, V, S> TreeNode<K, V, S> {
+    fn bst_property_holds(&self) -> bool {
+        /*
+            Verify the BST property holds for this node's subtree.
+        */
+        let left_ok = match &self.left {
+            Some(l) => l.key < self.key && l.bst_property_holds(),
+            _ => true,
+        };
+        let right_ok = match &self.right {
+            Some(r) => r.key > self.key && r.bst_property_holds(),
+            _ => true,
+        };
+        left_ok && right_ok
+    }
+}
+
+pub struct BalancedTree<K, V, A: Augment<K, V> = ()> {
+    /*
+        An ordered map that stays balanced by subtree size: the heavier side of
+        every node holds at most α = 2/3 of its subtree. When an insert or delete
+        pushes nodes on its path out of balance, the highest of them has its
+        subtree rebuilt into the perfectly balanced shape, which fixes every node
+        below it as well (scapegoat-style partial rebuilding). Updates take
+        amortized Θ(log n).
+
+        `strict()` trees instead keep |left_size - right_size| ≤ 1 at every node,
+        the shortest possible tree, at up to Θ(n) per update.
+
+        Q: Given a BST of size n constructed and maintained by these self-balancing
+           versions of INSERT and DELETE, what is the maximum height (in terms of n)?
+
+        A: Θ(log n). By default, with α = 2/3, height ≤ log_{3/2}(n) + 1 ≈
+           1.71 · log2(n) + 1. Strict trees have height ≤ ⌈log2(n + 1)⌉ due to the
+           fact that each node's left and right subtree sizes differ by at most 1.
+
+        Q: Why is |left - right| ≤ 1 not the default, with amortized Θ(log n)
+           updates as well?
+
+        A: No scheme can give both. For n = 2^k - 1 the only tree with
+           |left - right| ≤ 1 everywhere is the complete one, where each key's
+           position is fixed by its rank. Inserting a new smallest key and then
+           deleting the largest leaves n keys whose ranks all moved by one, so
+           every key has to change position: Ω(n) work for two updates, and the
+           pair can be repeated forever. The default therefore keeps the
+           logarithmic updates and gives up the exact balance; `strict()` makes
+           the opposite choice.
+
+        `A` chooses what every node summarizes about its subtree (see `augment`);
+        the default `()` keeps nothing beyond the counts.
+    */
+    root: Option<Box<TreeNode<K, V, A::Summary>>>,
+    alpha: (usize, usize),
+    stats: Option<Box<TreeStats>>,
+    observer: Option<Box<dyn TreeObserver<K>>>,
+}
+
+impl<K: Ord + Display, V, A: Augment<K, V>> Display for BalancedTree<K, V, A> {
+    /*
+       In rust we must implement the Display trait to be able to printa defined struct.
+
+       Will print the tree node in a readable format.
+
+       This allows us to print the tree in a readable format with simply calling println!("{}", tree);
+    */
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        fn fmt_node<K: Display, V, S>(
+            node: &TreeNode<K, V, S>,
+            f: &mut Formatter<'_>,
+            prefix: &str,
+            is_left: bool,
+        ) -> Result<(), Error> {
+            let connector = if prefix.is_empty() {
+                ""
+            } else if is_left {
+                "├── "
+            } else {
+                "└── "
+            };
+
+            let lr_label = if is_left { "L: " } else { "R: " };
+            let l_sz = TreeNode::subtree_size(&node.left);
+            let r_sz = TreeNode::subtree_size(&node.right);
+
+            writeln!(
+                f,
+                "{}{}{}{} [L:{} R:{}]",
+                prefix, connector, lr_label, node.key, l_sz, r_sz
+            )?;
+
+            let next_prefix_left = if prefix.is_empty() {
+                "    "
+            } else {
+                if is_left { "│   " } else { "    " }
+            };
+
+            let new_prefix_left = format!("{}{}", prefix, next_prefix_left);
+
+            if let Some(ref right) = node.right {
+                fmt_node(right, f, &new_prefix_left, false)?;
+            }
+
+            if let Some(ref left) = node.left {
+                fmt_node(left, f, &new_prefix_left, true)?;
+            }
+            Ok(())
+        }
+
+        if let Some(root) = &self.root {
+            writeln!(
+                f,
+                "Root: {} (bst_property_holds: {})",
+                root.key,
+                root.bst_property_holds()
+            )?;
+            let l_sz = TreeNode::subtree_size(&root.left);
+            let r_sz = TreeNode::subtree_size(&root.right);
+            writeln!(f, "{} [L:{} R:{}]", root.key, l_sz, r_sz)?;
+            if let Some(ref right) = root.right {
+                fmt_node(right, f, "", false)?;
+            }
+            if let Some(ref left) = root.left {
+                fmt_node(left, f, "", true)?;
+            }
+        } else {
+            writeln!(f, "Root: None")?;
+        }
+        Ok(())
+    }
+}
+
+impl<K: Ord, V, A: Augment<K, V>> Default for BalancedTree<K, V, A> {
+    fn default() -> Self {
+        Self::with_alpha((2, 3))
+    }
+}
+
+impl<K: Ord, V> BalancedTree<K, V> {
+    pub fn new() -> Self {
+        /*
+            Create a new, empty `BalancedTree` with amortized Θ(log n) updates, whose
+            nodes may hold up to 2/3 of their subtree on one side.
+        */
+        Self::with_alpha((2, 3))
+    }
+
+    pub fn strict() -> Self {
+        /*
+            Create a new, empty `BalancedTree` that keeps |left_size - right_size| ≤ 1
+            at every node. A single update may rebuild the whole tree, so updates
+            cost up to Θ(n) each, even on average.
+        */
+        Self::with_alpha((1, 2))
+    }
+
+    pub fn from_sorted<I: IntoIterator<Item = (K, V)>>(entries: I) -> Self {
+        /*
+            Build a perfectly size-balanced tree from entries whose keys are strictly
+            increasing, in O(n) and with every count filled in. Later updates keep
+            the default α = 2/3, as in `new()`.
+
+            Panics if the keys are not strictly increasing.
+        */
+        Self::build_sorted(entries.into_iter().collect(), (2, 3))
+    }
+
+    pub fn from_unsorted<I: IntoIterator<Item = (K, V)>>(entries: I) -> Self {
+        /*
+            Build a tree from entries in any order in O(n log n): sort, drop duplicate
+            keys, then `from_sorted`. As with repeated `insert`, the last value given
+            for a key wins.
+        */
+        Self::build_unsorted(entries.into_iter().collect(), (2, 3))
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        /*
+            Look up the value stored under `key` for in-place modification.
+
+            Only trees without an augmentation offer this: a summary could not be
+            brought up to date after the caller changes the value.
+        */
+        let mut cur = self.root.as_mut();
+        while let Some(node) = cur {
+            if *key < node.key {
+                cur = node.left.as_mut();
+            } else if *key > node.key {
+                cur = node.right.as_mut();
+            } else {
+                return Some(&mut node.value);
+            }
+        }
+        None
+    }
+}
+
+impl<K: Ord, V, A: Augment<K, V>> BalancedTree<K, V, A> {
+    pub fn with_augment(_augment: A) -> Self {
+        /*
+            Create a new, empty tree like `new()` whose nodes keep `A`'s summary of
+            their subtree, e.g. `BalancedTree::with_augment(Sum)`.
+        */
+        Self::with_alpha((2, 3))
+    }
+
+    pub fn strict_with_augment(_augment: A) -> Self {
+        /*
+            Create a new, empty tree like `strict()` whose nodes keep `A`'s summary
+            of their subtree.
+        */
+        Self::with_alpha((1, 2))
+    }
+
+    fn with_alpha(alpha: (usize, usize)) -> Self {
+        Self::with_root(None, alpha)
+    }
+
+    fn with_root(root: Option<Box<TreeNode<K, V, A::Summary>>>, alpha: (usize, usize)) -> Self {
+        /*
+            A tree around an existing root, with stats off and no observer.
+        */
+        Self {
+            root,
+            alpha,
+            stats: None,
+            observer: None,
+        }
+    }
+
+    fn build_sorted(entries: Vec<(K, V)>, alpha: (usize, usize)) -> Self {
+        /*
+            `from_sorted` for any augmentation and balance mode.
+        */
+        assert!(
+            entries.windows(2).all(|w| w[0].0 < w[1].0),
+            "from_sorted: keys must be strictly increasing"
+        );
+        let size = entries.len();
+        let mut nodes = entries.into_iter().map(|(k, v)| Self::leaf(k, v));
+        Self::with_root(Self::build(&mut nodes, size), alpha)
+    }
+
+    fn build_unsorted(mut entries: Vec<(K, V)>, alpha: (usize, usize)) -> Self {
+        /*
+            `from_unsorted` for any augmentation and balance mode.
+        */
+        // Reversing first makes the stable sort put the latest duplicate first
+        entries.reverse();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries.dedup_by(|later, earlier| later.0 == earlier.0);
+        Self::build_sorted(entries, alpha)
+    }
+
+    pub fn is_strict(&self) -> bool {
+        /*
+            Whether this tree was created by `strict()` and keeps |left - right| ≤ 1.
+        */
+        self.alpha == (1, 2)
+    }
+
+    pub fn len(&self) -> usize {
+        /*
+            Number of keys in the tree, read from the root's cached counts.
+        */
+        self.root
+            .as_ref()
+            .map_or(0, |r| 1 + r.left_count + r.right_count)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        /*
+            Whether the tree holds no keys.
+        */
+        self.root.is_none()
+    }
+
+    pub fn height(&self) -> usize {
+        /*
+            Number of nodes on the longest root-to-leaf path; 0 for an empty tree.
+            Read from the root's cached height in O(1).
+        */
+        self.root.as_ref().map_or(0, |r| r.height)
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        /*
+            Look up the value stored under `key`.
+        */
+        let mut cur = self.root.as_ref();
+        while let Some(node) = cur {
+            if *key < node.key {
+                cur = node.left.as_ref();
+            } else if *key > node.key {
+                cur = node.right.as_ref();
+            } else {
+                return Some(&node.value);
+            }
+        }
+        None
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        /*
+            Whether `key` is stored in the tree.
+        */
+        self.get(key).is_some()
+    }
+
+    pub fn update<F: FnOnce(&mut V)>(&mut self, key: &K, f: F) -> bool {
+        /*
+            Apply `f` to the value stored under `key` and bring the summaries on its
+            path up to date. Returns false, without calling `f`, if `key` is absent.
+
+            This is how augmented trees, which have no `get_mut`, edit a value in
+            place.
+        */
+        fn update_in<K: Ord, V, A: Augment<K, V>, F: FnOnce(&mut V)>(
+            node: &mut Option<Box<TreeNode<K, V, A::Summary>>>,
+            key: &K,
+            f: F,
+        ) -> bool {
+            let Some(n) = node.as_mut() else {
+                return false;
+            };
+            let found = if *key < n.key {
+                update_in::<K, V, A, F>(&mut n.left, key, f)
+            } else if *key > n.key {
+                update_in::<K, V, A, F>(&mut n.right, key, f)
+            } else {
+                f(&mut n.value);
+                true
+            };
+            if found {
+                BalancedTree::<K, V, A>::refresh(n);
+            }
+            found
+        }
+        update_in::<K, V, A, F>(&mut self.root, key, f)
+    }
+
+    pub fn select(&self, k: usize) -> Option<&K> {
+        /*
+            Return the k-th smallest key (0-based), or None if k ≥ len().
+
+            Walks down from the root using `left_count`: if k is smaller than the
+            left subtree the key is there, if it equals it the key is this node,
+            otherwise skip the left subtree and this node and continue right.
+            O(height), so O(log n) given the size-balance invariant.
+        */
+        let mut cur = self.root.as_ref();
+        let mut k = k;
+        while let Some(node) = cur {
+            if k < node.left_count {
+                cur = node.left.as_ref();
+            } else if k == node.left_count {
+                return Some(&node.key);
+            } else {
+                k -= node.left_count + 1;
+                cur = node.right.as_ref();
+            }
+        }
+        None
+    }
+
+    pub fn rank(&self, key: &K) -> usize {
+        /*
+            Count the keys strictly less than `key`. `key` does not need to be in
+            the tree; if it is, `select(rank(key))` returns it.
+
+            Every time the search goes right, the node and its whole left subtree
+            are smaller than `key`. O(height).
+        */
+        let mut cur = self.root.as_ref();
+        let mut below = 0;
+        while let Some(node) = cur {
+            if *key <= node.key {
+                cur = node.left.as_ref();
+            } else {
+                below += node.left_count + 1;
+                cur = node.right.as_ref();
+            }
+        }
+        below
+    }
+
+    fn rank_inclusive(&self, key: &K) -> usize {
+        /*
+            Count the keys less than or equal to `key`. O(height).
+        */
+        let mut cur = self.root.as_ref();
+        let mut through = 0;
+        while let Some(node) = cur {
+            if *key < node.key {
+                cur = node.left.as_ref();
+            } else {
+                through += node.left_count + 1;
+                cur = node.right.as_ref();
+            }
+        }
+        through
+    }
+
+    pub fn count_range(&self, low: &K, high: &K) -> usize {
+        /*
+            Count the keys k with low ≤ k < high, as the difference of two ranks.
+            An empty or inverted range counts 0.
+        */
+        self.rank(high).saturating_sub(self.rank(low))
+    }
+
+    pub fn first(&self) -> Option<(&K, &V)> {
+        /*
+            The entry with the smallest key: the end of the left spine.
+        */
+        let mut cur = self.root.as_ref()?;
+        while let Some(left) = cur.left.as_ref() {
+            cur = left;
+        }
+        Some((&cur.key, &cur.value))
+    }
+
+    pub fn last(&self) -> Option<(&K, &V)> {
+        /*
+            The entry with the largest key: the end of the right spine.
+        */
+        let mut cur = self.root.as_ref()?;
+        while let Some(right) = cur.right.as_ref() {
+            cur = right;
+        }
+        Some((&cur.key, &cur.value))
+    }
+
+    fn search_below(&self, key: &K, inclusive: bool) -> Option<(&K, &V)> {
+        /*
+            The entry with the largest key below `key` (or equal to it, if
+            `inclusive`): the last node on the search path where the search went right.
+        */
+        let mut cur = self.root.as_ref();
+        let mut best = None;
+        while let Some(node) = cur {
+            if node.key < *key || (inclusive && node.key == *key) {
+                best = Some((&node.key, &node.value));
+                cur = node.right.as_ref();
+            } else {
+                cur = node.left.as_ref();
+            }
+        }
+        best
+    }
+
+    fn search_above(&self, key: &K, inclusive: bool) -> Option<(&K, &V)> {
+        /*
+            The entry with the smallest key above `key` (or equal to it, if
+            `inclusive`): the last node on the search path where the search went left.
+        */
+        let mut cur = self.root.as_ref();
+        let mut best = None;
+        while let Some(node) = cur {
+            if node.key > *key || (inclusive && node.key == *key) {
+                best = Some((&node.key, &node.value));
+                cur = node.left.as_ref();
+            } else {
+                cur = node.right.as_ref();
+            }
+        }
+        best
+    }
+
+    pub fn floor(&self, key: &K) -> Option<(&K, &V)> {
+        /*
+            The entry with the largest key ≤ `key`. O(height).
+        */
+        self.search_below(key, true)
+    }
+
+    pub fn ceiling(&self, key: &K) -> Option<(&K, &V)> {
+        /*
+            The entry with the smallest key ≥ `key`. O(height).
+        */
+        self.search_above(key, true)
+    }
+
+    pub fn predecessor(&self, key: &K) -> Option<(&K, &V)> {
+        /*
+            The entry with the largest key < `key`. `key` does not need to be in
+            the tree. O(height).
+        */
+        self.search_below(key, false)
+    }
+
+    pub fn successor(&self, key: &K) -> Option<(&K, &V)> {
+        /*
+            The entry with the smallest key > `key`. `key` does not need to be in
+            the tree. O(height).
+        */
+        self.search_above(key, false)
+    }
+
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        /*
+            Remove and return the entry with the smallest key. Counts along the left
+            spine are updated and out-of-balance nodes rebuilt, as in `remove`.
+        */
+        let before = self.root_ptr();
+        let at_root = self.root.as_ref().is_some_and(|r| r.left.is_none());
+        let mut op = Op::new(&mut self.observer);
+        let removed = Self::remove_min(self.alpha, &mut self.root, true, &mut op);
+        let counts = op.counts;
+        self.finish_update(counts, before, at_root);
+        removed
+    }
+
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        /*
+            Remove and return the entry with the largest key.
+        */
+        let before = self.root_ptr();
+        let at_root = self.root.as_ref().is_some_and(|r| r.right.is_none());
+        let mut op = Op::new(&mut self.observer);
+        let removed = Self::remove_max(self.alpha, &mut self.root, true, &mut op);
+        let counts = op.counts;
+        self.finish_update(counts, before, at_root);
+        removed
+    }
+
+    fn too_heavy(alpha: (usize, usize), left: usize, right: usize) -> bool {
+        /*
+            Whether a node with subtrees of these sizes is out of balance: its heavier
+            side holds more than α = alpha.0 / alpha.1 of its subtree.
+        */
+        let (num, den) = alpha;
+        den * left.max(right) > num * (1 + left + right)
+    }
+
+    fn is_unbalanced(alpha: (usize, usize), node: &TreeNode<K, V, A::Summary>) -> bool {
+        /*
+            `too_heavy` for an existing node. Reads only the cached counts.
+        */
+        Self::too_heavy(alpha, node.left_count, node.right_count)
+    }
+
+    fn leaf(key: K, value: V) -> Box<TreeNode<K, V, A::Summary>> {
+        /*
+            A new childless node, summarizing just its own entry.
+        */
+        let summary = A::single(&key, &value);
+        Box::new(TreeNode::new(key, value, summary))
+    }
+
+    fn refresh(node: &mut TreeNode<K, V, A::Summary>) {
+        /*
+            Recompute a node's height and summary from its children's, the summary
+            in key order. Called on every node whose subtree changed, bottom up.
+        */
+        let child_height =
+            |c: &Option<Box<TreeNode<K, V, A::Summary>>>| c.as_ref().map_or(0, |c| c.height);
+        node.height = 1 + child_height(&node.left).max(child_height(&node.right));
+        let own = A::single(&node.key, &node.value);
+        let with_left = match &node.left {
+            Some(l) => A::combine(&l.summary, &own),
+            None => own,
+        };
+        node.summary = match &node.right {
+            Some(r) => A::combine(&with_left, &r.summary),
+            None => with_left,
+        };
+    }
+
+    fn rebuild(node: &mut Option<Box<TreeNode<K, V, A::Summary>>>, op: &mut Op<'_, K>) {
+        /*
+            Rebuild a subtree into the perfectly size-balanced shape in O(size), so
+            that |left_size - right_size| ≤ 1 holds at every node inside it.
+
+            The existing boxes are unlinked in key order and relinked, so no key or
+            value is moved or copied.
+        */
+        fn flatten<K, V, S>(
+            node: Option<Box<TreeNode<K, V, S>>>,
+            out: &mut Vec<Box<TreeNode<K, V, S>>>,
+        ) {
+            if let Some(mut n) = node {
+                flatten(n.left.take(), out);
+                let right = n.right.take();
+                out.push(n);
+                flatten(right, out);
+            }
+        }
+
+        let size = TreeNode::subtree_size(node);
+        op.counts.rebuilds += 1;
+        op.counts.nodes_rebuilt += size as u64;
+        // The old root keeps its rank, which finds it again for the observer
+        let old_rank = node.as_ref().map_or(0, |n| n.left_count);
+        let mut nodes = Vec::with_capacity(size);
+        flatten(node.take(), &mut nodes);
+        *node = Self::build(&mut nodes.into_iter(), size);
+        if op.observing()
+            && let Some(new_root) = node.as_deref()
+            && let Some(old_root) = cursor::node_at(Some(new_root), old_rank)
+        {
+            op.emit(TreeEvent::Rebuilt {
+                old_root: &old_root.key,
+                new_root: &new_root.key,
+                size,
+            });
+        }
+    }
+
+    fn build(
+        nodes: &mut impl Iterator<Item = Box<TreeNode<K, V, A::Summary>>>,
+        size: usize,
+    ) -> Option<Box<TreeNode<K, V, A::Summary>>> {
+        /*
+            Take the next `size` nodes (in key order) from `nodes` and link them into
+            a perfectly size-balanced subtree with its counts filled in.
+        */
+        if size == 0 {
+            return None;
+        }
+        let left_count = (size - 1) / 2;
+        let right_count = size - 1 - left_count;
+        let left = Self::build(nodes, left_count);
+        let mut n = nodes.next()?;
+        n.left = left;
+        n.left_count = left_count;
+        n.right = Self::build(nodes, right_count);
+        n.right_count = right_count;
+        Self::refresh(&mut n);
+        Some(n)
+    }
+
+    fn remove_max(
+        alpha: (usize, usize),
+        node: &mut Option<Box<TreeNode<K, V, A::Summary>>>,
+        check: bool,
+        op: &mut Op<'_, K>,
+    ) -> Option<(K, V)> {
+        /*
+            Unlink the maximum entry of a subtree and return it. The removed node's
+            left child takes its place. Counts are decremented on the way down, so
+            each node's new balance is known before descending below it: only the
+            highest node left out of balance is rebuilt. `check` is false below a
+            node that will be rebuilt anyway.
+        */
+        let n = node.as_mut()?;
+        op.counts.visits += 1;
+        if n.right.is_none() {
+            let mut n = node.take().unwrap();
+            *node = n.left.take();
+            if !op.moving_successor {
+                op.emit(TreeEvent::Deleted { key: &n.key });
+            }
+            return Some((n.key, n.value));
+        }
+        n.right_count -= 1;
+        let scapegoat = check && Self::is_unbalanced(alpha, n);
+        let removed = Self::remove_max(alpha, &mut n.right, check && !scapegoat, op);
+        Self::refresh(n);
+        if scapegoat {
+            Self::rebuild(node, op);
+        }
+        removed
+    }
+
+    fn remove_min(
+        alpha: (usize, usize),
+        node: &mut Option<Box<TreeNode<K, V, A::Summary>>>,
+        check: bool,
+        op: &mut Op<'_, K>,
+    ) -> Option<(K, V)> {
+        /*
+            Unlink the minimum entry of a subtree and return it. The removed node's
+            right child takes its place; otherwise as `remove_max`.
+        */
+        let n = node.as_mut()?;
+        op.counts.visits += 1;
+        if n.left.is_none() {
+            let mut n = node.take().unwrap();
+            *node = n.right.take();
+            if !op.moving_successor {
+                op.emit(TreeEvent::Deleted { key: &n.key });
+            }
+            return Some((n.key, n.value));
+        }
+        n.left_count -= 1;
+        let scapegoat = check && Self::is_unbalanced(alpha, n);
+        let removed = Self::remove_min(alpha, &mut n.left, check && !scapegoat, op);
+        Self::refresh(n);
+        if scapegoat {
+            Self::rebuild(node, op);
+        }
+        removed
+    }
+
+    fn insert_in(
+        alpha: (usize, usize),
+        node: &mut Option<Box<TreeNode<K, V, A::Summary>>>,
+        key: K,
+        value: V,
+        check: bool,
+        op: &mut Op<'_, K>,
+    ) -> Option<V> {
+        /*
+            BST insert that keeps the counts on the search path up to date. Counts
+            are raised on the way down as if the key were new, and lowered again on
+            the way back if it was already present. Each node's new balance is then
+            known before descending below it, so the highest node pushed out of
+            balance is found first; once the leaf is linked in below it, that
+            node's subtree is the only one rebuilt.
+        */
+        let Some(n) = node.as_mut() else {
+            let leaf = node.insert(Self::leaf(key, value));
+            op.emit(TreeEvent::Inserted { key: &leaf.key });
+            return None;
+        };
+        op.counts.visits += 1;
+        let go_left = match key.cmp(&n.key) {
+            Ordering::Less => true,
+            Ordering::Greater => false,
+            Ordering::Equal => {
+                let old = mem::replace(&mut n.value, value);
+                Self::refresh(n);
+                op.emit(TreeEvent::Updated { key: &n.key });
+                return Some(old);
+            }
+        };
+        if go_left {
+            n.left_count += 1;
+        } else {
+            n.right_count += 1;
+        }
+        let scapegoat = check && Self::is_unbalanced(alpha, n);
+        let child = if go_left { &mut n.left } else { &mut n.right };
+        let replaced = Self::insert_in(alpha, child, key, value, check && !scapegoat, op);
+        if replaced.is_some() {
+            // Only a value changed: no node was added below
+            if go_left {
+                n.left_count -= 1;
+            } else {
+                n.right_count -= 1;
+            }
+        }
+        // A replaced value changes the summaries on the path too
+        Self::refresh(n);
+        if replaced.is_none() && scapegoat {
+            Self::rebuild(node, op);
+        }
+        replaced
+    }
+
+    fn delete_in(
+        alpha: (usize, usize),
+        node: &mut Option<Box<TreeNode<K, V, A::Summary>>>,
+        locate: &mut impl FnMut(&TreeNode<K, V, A::Summary>) -> Ordering,
+        check: bool,
+        op: &mut Op<'_, K>,
+    ) -> Option<(K, V)> {
+        /*
+            BST delete that keeps the counts on the search path up to date. `locate`
+            is asked, once per node on the path, whether the entry to delete lies
+            left of (Less), right of (Greater) or at (Equal) that node; `remove`
+            compares keys, the cursor compares ranks.
+
+            As in `insert_in`, counts are lowered on the way down and restored if
+            nothing is found, and only the highest node left out of balance is
+            rebuilt. Returns the removed entry if a node was removed.
+        */
+        let n = node.as_mut()?;
+        op.counts.visits += 1;
+        let (scapegoat, removed) = match locate(n) {
+            order @ (Ordering::Less | Ordering::Greater) => {
+                let go_left = order == Ordering::Less;
+                if (if go_left { &n.left } else { &n.right }).is_none() {
+                    return None;
+                }
+                if go_left {
+                    n.left_count -= 1;
+                } else {
+                    n.right_count -= 1;
+                }
+                let scapegoat = check && Self::is_unbalanced(alpha, n);
+                let child = if go_left { &mut n.left } else { &mut n.right };
+                let removed = Self::delete_in(alpha, child, locate, check && !scapegoat, op);
+                if removed.is_none() {
+                    // Nothing to delete: put the count back, nothing else changed
+                    if go_left {
+                        n.left_count += 1;
+                    } else {
+                        n.right_count += 1;
+                    }
+                    return None;
+                }
+                (scapegoat, removed)
+            }
+            Ordering::Equal if n.left.is_some() && n.right.is_some() => {
+                // both children exist: replace with successor (min of right)
+                op.emit(TreeEvent::Deleted { key: &n.key });
+                n.right_count -= 1;
+                let scapegoat = check && Self::is_unbalanced(alpha, n);
+                op.moving_successor = true;
+                let successor = Self::remove_min(alpha, &mut n.right, check && !scapegoat, op);
+                op.moving_successor = false;
+                op.counts.successor_swaps += 1;
+                let (succ_key, succ_value) = successor.unwrap();
+                (scapegoat, Some(n.replace_entry(succ_key, succ_value)))
+            }
+            Ordering::Equal => {
+                let mut cur = node.take().unwrap();
+                *node = cur.left.take().or_else(|| cur.right.take());
+                op.emit(TreeEvent::Deleted { key: &cur.key });
+                return Some((cur.key, cur.value));
+            }
+        };
+        Self::refresh(n);
+        if scapegoat {
+            Self::rebuild(node, op);
+        }
+        removed
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        /*
+            Insert a key, or replace the value of a key that is already present and
+            return the old value.
+
+            Q: What are the running times, in asymptotic notation, for INSERT?
+
+            A: Θ(log n) to descend and update the counts on the path, plus at most
+               one rebuild. By default a subtree of size m is rebuilt in Θ(m) only
+               after Ω(m) updates below it since its last rebuild, so INSERT is
+               amortized Θ(log n). In `strict()` trees that rebuild can take the
+               whole tree, so the worst case is Θ(n), and (see the type's doc) no
+               better amortized.
+
+            Q: Are the best and worst cases different asymptotically compared to DELETE.
+
+            A: Same as DELETE.
+        */
+        let before = self.root_ptr();
+        let mut op = Op::new(&mut self.observer);
+        let replaced = Self::insert_in(self.alpha, &mut self.root, key, value, true, &mut op);
+        let counts = op.counts;
+        self.finish_update(counts, before, false);
+        replaced
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        /*
+            Delete a key and return its value.
+
+            Q: What are the running times, in asymptotic notation, for DELETE?
+
+            A: Θ(log n) to find the key and its successor, plus at most one
+               rebuild: amortized Θ(log n) by default, where rebuilds are paid for
+               by earlier updates, and worst case Θ(n) in `strict()` trees.
+
+            Q: Are the best and worst cases different asymptotically compared to Insert?
+
+            A: Same as INSERT.
+        */
+        let before = self.root_ptr();
+        let at_root = self.root.as_ref().is_some_and(|r| r.key == *key);
+        let mut op = Op::new(&mut self.observer);
+        let removed = Self::delete_in(
+            self.alpha,
+            &mut self.root,
+            &mut |n| key.cmp(&n.key),
+            true,
+            &mut op,
+        );
+        let counts = op.counts;
+        self.finish_update(counts, before, at_root);
+        removed.map(|(_, v)| v)
+    }
+
+    fn root_ptr(&self) -> *const TreeNode<K, V, A::Summary> {
+        /*
+            Address of the root node, or null; only ever compared, never read.
+        */
+        self.root.as_deref().map_or(ptr::null(), |r| r as *const _)
+    }
+
+    pub(crate) fn finish_update(
+        &mut self,
+        counts: OpCounts,
+        before: *const TreeNode<K, V, A::Summary>,
+        removed_root: bool,
+    ) {
+        /*
+            Close an update: tell the observer if the root changed, either to
+            another node or by deleting the root's own entry, and add the counts
+            to the stats.
+        */
+        let replaced = removed_root || !ptr::eq(before, self.root_ptr());
+        if replaced && let Some(observer) = self.observer.as_mut() {
+            observer.notify(TreeEvent::RootReplaced {
+                root: self.root.as_ref().map(|r| &r.key),
+            });
+        }
+        self.record(counts);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    // Deterministic xorshift64 generator for the randomized tests.
+    pub(crate) fn xorshift(mut seed: u64) -> impl FnMut() -> u64 {
+        move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        }
+    }
+
+    // Runs the full invariant checker; every test calls this after each update.
+    pub(crate) fn assert_valid<K: Ord + Clone + std::fmt::Debug, V, A: Augment<K, V>>(
+        tree: &BalancedTree<K, V, A>,
+    ) {
+        fn check<K, V, S>(node: &Option<Box<TreeNode<K, V, S>>>) -> usize {
+            match node {
+                None => 0,
+                Some(n) => {
+                    let l = check(&n.left);
+                    let r = check(&n.right);
+                    assert!(l.abs_diff(r) <= 1);
+                    1 + l + r
+                }
+            }
+        }
+        assert_eq!(tree.validate(), Ok(()));
+        // Strict trees keep |left - right| ≤ 1 at every node
+        if tree.is_strict() {
+            check(&tree.root);
+        }
+    }
+
+    #[test]
+    fn test_map_api() {
+        let mut tree = BalancedTree::new();
+        assert!(tree.is_empty());
+
+        assert_eq!(tree.insert(5, "five"), None);
+        assert_valid(&tree);
+        assert_eq!(tree.insert(3, "three"), None);
+        assert_valid(&tree);
+        assert_eq!(tree.insert(8, "eight"), None);
+        assert_valid(&tree);
+        assert_eq!(tree.insert(3, "THREE"), Some("three"));
+        assert_valid(&tree);
+        assert_eq!(tree.len(), 3);
+
+        assert_eq!(tree.get(&3), Some(&"THREE"));
+        assert_eq!(tree.get(&4), None);
+        assert!(tree.contains_key(&8));
+        assert!(!tree.contains_key(&9));
+
+        if let Some(v) = tree.get_mut(&8) {
+            *v = "ate";
+        }
+        assert_eq!(tree.get(&8), Some(&"ate"));
+
+        assert_eq!(tree.remove(&5), Some("five"));
+        assert_valid(&tree);
+        assert_eq!(tree.remove(&5), None);
+        assert_valid(&tree);
+        assert_eq!(tree.len(), 2);
+    }
+
+    #[test]
+    fn test_non_copy_keys_and_values() {
+        let mut tree: BalancedTree<String, Vec<u32>> = BalancedTree::new();
+        for (i, word) in ["pear", "apple", "fig", "kiwi", "banana", "cherry"]
+            .iter()
+            .enumerate()
+        {
+            tree.insert(word.to_string(), vec![i as u32]);
+            assert_valid(&tree);
+        }
+        tree.get_mut(&"fig".to_string()).unwrap().push(10);
+        assert_valid(&tree);
+        assert_eq!(tree.get(&"fig".to_string()), Some(&vec![2, 10]));
+        assert_eq!(tree.remove(&"apple".to_string()), Some(vec![1]));
+        assert_valid(&tree);
+    }
+
+    #[test]
+    fn test_matches_btreemap() {
+        let mut next = xorshift(0x853c_49e6_748f_ea9b);
+        let mut tree = BalancedTree::new();
+        let mut model = BTreeMap::new();
+        for step in 0..2000u64 {
+            let key = (next() % 200) as i32;
+            if next().is_multiple_of(3) {
+                assert_eq!(tree.remove(&key), model.remove(&key));
+            } else {
+                assert_eq!(tree.insert(key, step), model.insert(key, step));
+            }
+            assert_valid(&tree);
+            assert_eq!(tree.len(), model.len());
+        }
+        for (k, v) in &model {
+            assert_eq!(tree.get(k), Some(v));
+        }
+    }
+
+    #[test]
+    fn test_order_statistics() {
+        let mut tree = BalancedTree::new();
+        for key in [50, 10, 40, 20, 30] {
+            tree.insert(key, ());
+            assert_valid(&tree);
+        }
+        assert_eq!(tree.select(0), Some(&10));
+        assert_eq!(tree.select(4), Some(&50));
+        assert_eq!(tree.select(5), None);
+
+        assert_eq!(tree.rank(&10), 0);
+        assert_eq!(tree.rank(&35), 3);
+        assert_eq!(tree.rank(&99), 5);
+
+        // Half-open: 20 and 30 counted, 40 not
+        assert_eq!(tree.count_range(&20, &40), 2);
+        assert_eq!(tree.count_range(&40, &20), 0);
+        assert_eq!(tree.count_range(&0, &100), 5);
+    }
+
+    #[test]
+    fn test_order_statistics_match_sorted_keys() {
+        let mut next = xorshift(0x2545_f491_4f6c_dd1d);
+        let mut tree = BalancedTree::new();
+        let mut model = BTreeMap::new();
+        for _ in 0..500 {
+            let key = (next() % 300) as i32;
+            if next().is_multiple_of(4) {
+                tree.remove(&key);
+                model.remove(&key);
+            } else {
+                tree.insert(key, ());
+                model.insert(key, ());
+            }
+            assert_valid(&tree);
+        }
+        let keys: Vec<i32> = model.keys().copied().collect();
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(tree.select(i), Some(key));
+            assert_eq!(tree.rank(key), i);
+        }
+        for low in (-10..310).step_by(7) {
+            let high = low + 40;
+            let expected = keys.iter().filter(|&&k| low <= k && k < high).count();
+            assert_eq!(tree.count_range(&low, &high), expected);
+        }
+    }
+
+    #[test]
+    fn test_strict_mode_keeps_perfect_size_balance() {
+        let mut next = xorshift(0x9e37_79b9_7f4a_7c15);
+        let mut tree = BalancedTree::strict();
+        assert!(tree.is_strict());
+        for _ in 0..1000 {
+            let key = next() % 150;
+            if next().is_multiple_of(3) {
+                tree.remove(&key);
+            } else {
+                tree.insert(key, ());
+            }
+            assert_valid(&tree);
+            assert!(tree.height() <= tree.height_bound());
+        }
+    }
+
+    #[test]
+    fn test_sorted_insertions_stay_logarithmic() {
+        for strict in [true, false] {
+            let mut tree = if strict {
+                BalancedTree::strict()
+            } else {
+                BalancedTree::new()
+            };
+            for key in 0..10_000 {
+                tree.insert(key, ());
+                assert_valid(&tree);
+            }
+            // ⌈log2(10_001)⌉ = 14, or log_{3/2}(10_000) + 1 ≈ 23.7 with α = 2/3
+            assert!(tree.height() <= if strict { 14 } else { 23 });
+            for key in (0..10_000).step_by(2) {
+                tree.remove(&key);
+                assert_valid(&tree);
+            }
+            assert_eq!(tree.len(), 5_000);
+            assert_eq!(tree.select(0), Some(&1));
+        }
+    }
+
+    #[test]
+    fn test_navigation() {
+        let mut tree: BalancedTree<i32, char> = BalancedTree::new();
+        assert_eq!(tree.first(), None);
+        assert_eq!(tree.floor(&3), None);
+        assert_eq!(tree.pop_first(), None);
+        assert_valid(&tree);
+        for (k, c) in [(10, 'a'), (20, 'b'), (30, 'c'), (40, 'd')] {
+            tree.insert(k, c);
+            assert_valid(&tree);
+        }
+        assert_eq!(tree.first(), Some((&10, &'a')));
+        assert_eq!(tree.last(), Some((&40, &'d')));
+
+        assert_eq!(tree.floor(&20), Some((&20, &'b')));
+        assert_eq!(tree.floor(&25), Some((&20, &'b')));
+        assert_eq!(tree.floor(&5), None);
+        assert_eq!(tree.ceiling(&25), Some((&30, &'c')));
+        assert_eq!(tree.ceiling(&45), None);
+        assert_eq!(tree.predecessor(&20), Some((&10, &'a')));
+        assert_eq!(tree.predecessor(&10), None);
+        assert_eq!(tree.successor(&20), Some((&30, &'c')));
+        assert_eq!(tree.successor(&35), Some((&40, &'d')));
+        assert_eq!(tree.successor(&40), None);
+    }
+
+    #[test]
+    fn test_pop_keeps_balance() {
+        for mut tree in [BalancedTree::strict(), BalancedTree::new()] {
+            for key in 0..200 {
+                tree.insert(key, ());
+                assert_valid(&tree);
+            }
+            for i in 0..100 {
+                assert_eq!(tree.pop_first(), Some((i, ())));
+                assert_valid(&tree);
+                assert_eq!(tree.pop_last(), Some((199 - i, ())));
+                assert_valid(&tree);
+            }
+            assert!(tree.is_empty());
+            assert_eq!(tree.pop_last(), None);
+            assert_valid(&tree);
+        }
+    }
+
+    #[test]
+    fn test_from_sorted() {
+        let tree = BalancedTree::from_sorted((0..1000).map(|k| (k, k * 2)));
+        assert_valid(&tree);
+        assert_eq!(tree.len(), 1000);
+        // Perfect size balance: height is exactly ⌈log2(n + 1)⌉
+        assert_eq!(tree.height(), 10);
+        assert_eq!(tree.get(&500), Some(&1000));
+        assert_eq!(tree.select(999), Some(&999));
+
+        let empty: BalancedTree<i32, ()> = BalancedTree::from_sorted(Vec::new());
+        assert_valid(&empty);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "strictly increasing")]
+    fn test_from_sorted_rejects_unsorted_input() {
+        BalancedTree::from_sorted([(1, ()), (1, ())]);
+    }
+
+    #[test]
+    fn test_from_unsorted_keeps_last_duplicate() {
+        let entries = [(3, 'a'), (1, 'b'), (3, 'c'), (2, 'd'), (1, 'e')];
+        let mut tree = BalancedTree::from_unsorted(entries);
+        assert_valid(&tree);
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.get(&1), Some(&'e'));
+        assert_eq!(tree.get(&3), Some(&'c'));
+
+        // Later updates keep working on a bulk-built tree
+        for key in 4..100 {
+            tree.insert(key, 'z');
+            assert_valid(&tree);
+        }
+        tree.remove(&2);
+        assert_valid(&tree);
+    }
+}
@@ -0,0 +1,303 @@
+/*
+    Multiset
+        a BalancedTree that counts repeated keys instead of dropping them
+
+    Each distinct key is stored once, with its multiplicity as the value, so the
+    tree's shape and its balance guarantee depend only on the number of distinct
+    keys: a million copies of one reading cost one node. The `Sum` augmentation
+    keeps the total multiplicity of every subtree next to the node counts, which
+    is what rank and select need to count duplicates in O(log n).
+
+    Example: {3, 3, 5, 7, 7, 7}
+        count(7) = 3, len() = 6, distinct_len() = 3
+        rank(7) = 3 (elements below 7), select(4) = 7 (0-based, duplicates counted)
+*/
+
+use crate::{BalancedTree, augment::Sum};
+
+pub struct Multiset<K> {
+    /*
+        An ordered multiset. Keys are compared with `Ord`; equal keys are
+        indistinguishable, so only the first copy inserted is kept.
+    */
+    tree: BalancedTree<K, usize, Sum>,
+}
+
+impl<K: Ord> Default for Multiset<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord> Multiset<K> {
+    pub fn new() -> Self {
+        /*
+            Create a new, empty multiset with amortized Θ(log n) updates, where n is
+            the number of distinct keys, as in `BalancedTree::new()`.
+        */
+        Self {
+            tree: BalancedTree::with_augment(Sum),
+        }
+    }
+
+    pub fn strict() -> Self {
+        /*
+            Create a new, empty multiset whose distinct keys are kept perfectly
+            size-balanced, as in `BalancedTree::strict()`.
+        */
+        Self {
+            tree: BalancedTree::strict_with_augment(Sum),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        /*
+            Number of elements, counting every copy, read from the root's summary.
+        */
+        self.tree.summary()
+    }
+
+    pub fn distinct_len(&self) -> usize {
+        /*
+            Number of distinct keys, which is also the number of nodes.
+        */
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        /*
+            Whether the multiset holds no elements.
+        */
+        self.tree.is_empty()
+    }
+
+    pub fn height(&self) -> usize {
+        /*
+            Height of the underlying tree of distinct keys.
+        */
+        self.tree.height()
+    }
+
+    pub fn count(&self, key: &K) -> usize {
+        /*
+            How many copies of `key` are stored.
+        */
+        self.tree.get(key).copied().unwrap_or(0)
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        /*
+            Whether at least one copy of `key` is stored.
+        */
+        self.tree.contains_key(key)
+    }
+
+    pub fn insert(&mut self, key: K) -> usize {
+        /*
+            Add one copy of `key` and return its new count.
+        */
+        self.insert_many(key, 1)
+    }
+
+    pub fn insert_many(&mut self, key: K, copies: usize) -> usize {
+        /*
+            Add `copies` copies of `key` and return its new count. Adding 0 copies
+            of an absent key stores nothing.
+        */
+        let mut count = copies;
+        let found = self.tree.update(&key, |c| {
+            *c += copies;
+            count = *c;
+        });
+        if !found && copies > 0 {
+            self.tree.insert(key, copies);
+        }
+        count
+    }
+
+    pub fn remove_one(&mut self, key: &K) -> bool {
+        /*
+            Remove one copy of `key`. Returns false if there was none. The node is
+            only deleted when its last copy goes.
+        */
+        match self.count(key) {
+            0 => false,
+            1 => self.tree.remove(key).is_some(),
+            _ => self.tree.update(key, |c| *c -= 1),
+        }
+    }
+
+    pub fn remove_all(&mut self, key: &K) -> usize {
+        /*
+            Remove every copy of `key` and return how many there were.
+        */
+        self.tree.remove(key).unwrap_or(0)
+    }
+
+    pub fn rank(&self, key: &K) -> usize {
+        /*
+            Number of elements strictly smaller than `key`, counting duplicates,
+            in O(log n).
+        */
+        self.tree.range_sum(..key)
+    }
+
+    pub fn select(&self, mut i: usize) -> Option<&K> {
+        /*
+            The i-th smallest element (0-based), counting duplicates, or None if
+            i ≥ len(). Walks down using the subtree totals, like
+            `BalancedTree::select` does with the counts.
+        */
+        let mut cur = self.tree.root.as_deref();
+        while let Some(node) = cur {
+            let below = node.left.as_ref().map_or(0, |l| l.summary);
+            if i < below {
+                cur = node.left.as_deref();
+            } else if i < below + node.value {
+                return Some(&node.key);
+            } else {
+                i -= below + node.value;
+                cur = node.right.as_deref();
+            }
+        }
+        None
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &K> {
+        /*
+            Iterate over every element in order, repeating each key by its count.
+        */
+        self.tree
+            .iter()
+            .flat_map(|(key, &count)| std::iter::repeat_n(key, count))
+    }
+
+    pub fn counts(&self) -> impl Iterator<Item = (&K, usize)> {
+        /*
+            Iterate over the distinct keys in order with their counts.
+        */
+        self.tree.iter().map(|(key, &count)| (key, count))
+    }
+}
+
+impl<K: Ord> FromIterator<K> for Multiset<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<K: Ord> Extend<K> for Multiset<K> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        for key in iter {
+            self.insert(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{assert_valid, xorshift};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_counts_duplicates() {
+        let mut readings: Multiset<u32> = [7, 3, 7, 5, 3, 7].into_iter().collect();
+        assert_valid(&readings.tree);
+        assert_eq!(readings.len(), 6);
+        assert_eq!(readings.distinct_len(), 3);
+        assert_eq!(readings.count(&7), 3);
+        assert_eq!(readings.count(&4), 0);
+        assert_eq!(
+            readings.iter().copied().collect::<Vec<_>>(),
+            [3, 3, 5, 7, 7, 7]
+        );
+        assert_eq!(
+            readings.counts().collect::<Vec<_>>(),
+            [(&3, 2), (&5, 1), (&7, 3)]
+        );
+
+        assert_eq!(readings.rank(&7), 3);
+        assert_eq!(readings.rank(&3), 0);
+        assert_eq!(readings.rank(&100), 6);
+        let selected: Vec<_> = (0..7).map(|i| readings.select(i).copied()).collect();
+        assert_eq!(
+            selected,
+            [Some(3), Some(3), Some(5), Some(7), Some(7), Some(7), None]
+        );
+
+        assert!(readings.remove_one(&7));
+        assert_valid(&readings.tree);
+        assert_eq!(readings.count(&7), 2);
+        assert!(readings.remove_one(&5));
+        assert!(!readings.remove_one(&5));
+        assert!(!readings.contains(&5));
+        assert_eq!(readings.remove_all(&3), 2);
+        assert_eq!(readings.remove_all(&3), 0);
+        assert_valid(&readings.tree);
+        assert_eq!(readings.iter().copied().collect::<Vec<_>>(), [7, 7]);
+        assert_eq!(readings.insert_many(9, 0), 0);
+        assert!(!readings.contains(&9));
+    }
+
+    #[test]
+    fn test_balance_depends_on_distinct_keys() {
+        let mut readings = Multiset::new();
+        for i in 0..100_000u32 {
+            readings.insert(i % 7);
+        }
+        assert_valid(&readings.tree);
+        assert_eq!(readings.len(), 100_000);
+        assert_eq!(readings.distinct_len(), 7);
+        // 7 distinct keys fit in a perfect tree of height 3
+        assert_eq!(readings.height(), 3);
+        assert_eq!(readings.select(99_999), Some(&6));
+        assert_eq!(readings.rank(&6), 100_000 - readings.count(&6));
+    }
+
+    #[test]
+    fn test_matches_counting_map() {
+        let mut next = xorshift(0x5e45_0a7e_d0d0_beef);
+        for mut set in [Multiset::new(), Multiset::strict()] {
+            let mut model: BTreeMap<u32, usize> = BTreeMap::new();
+            for step in 0..3000 {
+                let key = (next() % 60) as u32;
+                match next() % 4 {
+                    0 | 1 => {
+                        *model.entry(key).or_default() += 1;
+                        assert_eq!(set.insert(key), model[&key]);
+                    }
+                    2 => {
+                        let had = model.get(&key).copied().unwrap_or(0);
+                        match had {
+                            0 => {}
+                            1 => drop(model.remove(&key)),
+                            _ => drop(model.insert(key, had - 1)),
+                        }
+                        assert_eq!(set.remove_one(&key), had > 0);
+                    }
+                    _ => assert_eq!(set.remove_all(&key), model.remove(&key).unwrap_or(0)),
+                }
+                assert_valid(&set.tree);
+                assert_eq!(set.distinct_len(), model.len());
+                if step % 50 == 0 {
+                    let flat: Vec<u32> = model
+                        .iter()
+                        .flat_map(|(&k, &c)| std::iter::repeat_n(k, c))
+                        .collect();
+                    assert_eq!(set.len(), flat.len());
+                    assert!(set.iter().eq(flat.iter()));
+                    for (i, key) in flat.iter().enumerate() {
+                        assert_eq!(set.select(i), Some(key));
+                    }
+                    assert_eq!(set.select(flat.len()), None);
+                    for key in 0..61 {
+                        assert_eq!(set.rank(&key), flat.partition_point(|&k| k < key));
+                    }
+                }
+            }
+        }
+    }
+}
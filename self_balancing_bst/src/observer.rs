@@ -0,0 +1,374 @@
+/*
+    Observers
+        a hook that is told what each update did to the tree
+
+    An observer set with `set_observer` receives one event per step of an update,
+    in the order the steps happen:
+
+        Inserted / Updated   the key entered the tree, or its value was replaced
+        Deleted              the key left the tree
+        Rebuilt              a subtree out of balance was rebuilt on the way back up
+        RootReplaced         the update left a different entry at the root (last)
+
+    Updates are insert, remove, pop_first, pop_last and removal through a cursor.
+    Events borrow the keys from the tree, so observing costs no clones; a tree
+    without an observer only checks for one. Observers must be Send and Sync so
+    that a tree holding one can still move between or be shared across threads.
+    `ConsoleObserver` prints the teaching output of the demo, and
+    `RecordingObserver` keeps owned copies of the events for tests.
+*/
+
+use std::{
+    fmt::Display,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use crate::{BalancedTree, augment::Augment, stats::OpCounts};
+
+/// TreeEvent
+///
+/// One step of an update. Observers receive `TreeEvent<&K>`, borrowing from the
+/// tree; `cloned()` turns that into an owned `TreeEvent<K>`.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeEvent<K> {
+    Inserted {
+        key: K,
+    },
+    Updated {
+        key: K,
+    },
+    Deleted {
+        key: K,
+    },
+    Rebuilt {
+        old_root: K,
+        new_root: K,
+        size: usize,
+    },
+    RootReplaced {
+        root: Option<K>,
+    },
+}
+
+impl<K: Clone> TreeEvent<&K> {
+    /// cloned()
+    ///
+    /// Returns the same event with its keys cloned.
+    ///
+    pub fn cloned(self) -> TreeEvent<K> {
+        match self {
+            TreeEvent::Inserted { key } => TreeEvent::Inserted { key: key.clone() },
+            TreeEvent::Updated { key } => TreeEvent::Updated { key: key.clone() },
+            TreeEvent::Deleted { key } => TreeEvent::Deleted { key: key.clone() },
+            TreeEvent::Rebuilt {
+                old_root,
+                new_root,
+                size,
+            } => TreeEvent::Rebuilt {
+                old_root: old_root.clone(),
+                new_root: new_root.clone(),
+                size,
+            },
+            TreeEvent::RootReplaced { root } => TreeEvent::RootReplaced {
+                root: root.cloned(),
+            },
+        }
+    }
+}
+
+/// TreeObserver
+///
+/// Receives the events of every update made to the tree it is set on.
+///
+pub trait TreeObserver<K>: Send + Sync {
+    fn notify(&mut self, event: TreeEvent<&K>);
+}
+
+/// ConsoleObserver
+///
+/// Prints `Insert: {key}` for every insert, as the teaching demo does. The
+/// verbose mode also prints deletes, rebuilds and root changes.
+///
+#[derive(Debug, Clone, Default)]
+pub struct ConsoleObserver {
+    verbose: bool,
+}
+
+impl ConsoleObserver {
+    /// new()
+    ///
+    /// Returns an observer that prints only inserts.
+    ///
+    pub fn new() -> Self {
+        Self { verbose: false }
+    }
+
+    /// verbose()
+    ///
+    /// Returns an observer that prints every event.
+    ///
+    pub fn verbose() -> Self {
+        Self { verbose: true }
+    }
+}
+
+impl<K: Display> TreeObserver<K> for ConsoleObserver {
+    fn notify(&mut self, event: TreeEvent<&K>) {
+        match event {
+            TreeEvent::Inserted { key } | TreeEvent::Updated { key } => println!("Insert: {}", key),
+            _ if !self.verbose => {}
+            TreeEvent::Deleted { key } => println!("Delete: {}", key),
+            TreeEvent::Rebuilt {
+                old_root,
+                new_root,
+                size,
+            } => {
+                println!(
+                    "Rebuild: {} nodes under {}, new root {}",
+                    size, old_root, new_root
+                )
+            }
+            TreeEvent::RootReplaced { root: Some(root) } => println!("Root: {}", root),
+            TreeEvent::RootReplaced { root: None } => println!("Root: None"),
+        }
+    }
+}
+
+/// RecordingObserver
+///
+/// Keeps an owned copy of every event. Clones share one log, so a test can keep
+/// a clone and read the events after handing the observer to a tree, even one
+/// on another thread.
+///
+#[derive(Debug)]
+pub struct RecordingObserver<K> {
+    events: Arc<Mutex<Vec<TreeEvent<K>>>>,
+}
+
+impl<K> Clone for RecordingObserver<K> {
+    fn clone(&self) -> Self {
+        Self {
+            events: Arc::clone(&self.events),
+        }
+    }
+}
+
+impl<K> Default for RecordingObserver<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K> RecordingObserver<K> {
+    /// new()
+    ///
+    /// Returns an observer with an empty log.
+    ///
+    pub fn new() -> Self {
+        Self {
+            events: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// take()
+    ///
+    /// Returns the events recorded so far and empties the log.
+    ///
+    pub fn take(&self) -> Vec<TreeEvent<K>> {
+        std::mem::take(&mut *self.log())
+    }
+
+    // A panic while the log was held cannot leave it half-written, so a
+    // poisoned lock is still safe to use
+    fn log(&self) -> std::sync::MutexGuard<'_, Vec<TreeEvent<K>>> {
+        self.events.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<K: Clone + Send> TreeObserver<K> for RecordingObserver<K> {
+    fn notify(&mut self, event: TreeEvent<&K>) {
+        self.log().push(event.cloned());
+    }
+}
+
+// What the internal update functions carry along: the counts for `stats` and
+// the observer, if any
+pub(crate) struct Op<'o, K> {
+    pub(crate) counts: OpCounts,
+    observer: Option<&'o mut dyn TreeObserver<K>>,
+    // Set while a delete unlinks the successor it moves up, which is not a
+    // deletion of that key
+    pub(crate) moving_successor: bool,
+}
+
+impl<'o, K> Op<'o, K> {
+    pub(crate) fn new(observer: &'o mut Option<Box<dyn TreeObserver<K>>>) -> Self {
+        let observer = observer
+            .as_deref_mut()
+            .map(|o| o as &mut dyn TreeObserver<K>);
+        Self {
+            counts: OpCounts::default(),
+            observer,
+            moving_successor: false,
+        }
+    }
+
+    pub(crate) fn silent() -> Self {
+        Self {
+            counts: OpCounts::default(),
+            observer: None,
+            moving_successor: false,
+        }
+    }
+
+    pub(crate) fn observing(&self) -> bool {
+        self.observer.is_some()
+    }
+
+    pub(crate) fn emit(&mut self, event: TreeEvent<&K>) {
+        if let Some(observer) = self.observer.as_mut() {
+            observer.notify(event);
+        }
+    }
+}
+
+impl<K: Ord, V, A: Augment<K, V>> BalancedTree<K, V, A> {
+    pub fn set_observer(&mut self, observer: impl TreeObserver<K> + 'static) {
+        /*
+            Report the steps of every later update to `observer`, replacing any
+            observer set before.
+        */
+        self.observer = Some(Box::new(observer));
+    }
+
+    pub fn take_observer(&mut self) -> Option<Box<dyn TreeObserver<K>>> {
+        /*
+            Remove the observer and hand it back, or None if there was none.
+        */
+        self.observer.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{assert_valid, xorshift};
+    use TreeEvent::*;
+
+    #[test]
+    fn test_reports_each_step_in_order() {
+        let log = RecordingObserver::new();
+        let mut tree = BalancedTree::strict();
+        tree.set_observer(log.clone());
+
+        tree.insert(1, "a");
+        assert_eq!(
+            log.take(),
+            [Inserted { key: 1 }, RootReplaced { root: Some(1) }]
+        );
+        tree.insert(2, "b");
+        assert_eq!(log.take(), [Inserted { key: 2 }]);
+        // 1 → 2 → 3 is out of balance at 1 and gets rebuilt around 2
+        tree.insert(3, "c");
+        assert_eq!(
+            log.take(),
+            [
+                Inserted { key: 3 },
+                Rebuilt {
+                    old_root: 1,
+                    new_root: 2,
+                    size: 3
+                },
+                RootReplaced { root: Some(2) },
+            ]
+        );
+        tree.insert(3, "d");
+        assert_eq!(log.take(), [Updated { key: 3 }]);
+        // Removing the root moves its successor up in place
+        tree.remove(&2);
+        assert_eq!(
+            log.take(),
+            [Deleted { key: 2 }, RootReplaced { root: Some(3) }]
+        );
+        tree.remove(&2);
+        assert_eq!(log.take(), []);
+        tree.pop_first();
+        assert_eq!(log.take(), [Deleted { key: 1 }]);
+        tree.pop_last();
+        assert_eq!(
+            log.take(),
+            [Deleted { key: 3 }, RootReplaced { root: None }]
+        );
+
+        // Without the observer nothing is reported
+        assert!(tree.take_observer().is_some());
+        tree.insert(4, "e");
+        assert_eq!(log.take(), []);
+    }
+
+    #[test]
+    fn test_events_replay_the_updates() {
+        let mut next = xorshift(0x0b5e_77e2_5eed_cafe);
+        for strict in [false, true] {
+            let log = RecordingObserver::new();
+            let mut tree = if strict {
+                BalancedTree::strict()
+            } else {
+                BalancedTree::new()
+            };
+            tree.set_observer(log.clone());
+            tree.enable_stats();
+            let mut keys = std::collections::BTreeSet::new();
+            for _ in 0..2000 {
+                let key = (next() % 300) as u32;
+                match next() % 4 {
+                    0 | 1 => drop(tree.insert(key, ())),
+                    2 => drop(tree.remove(&key)),
+                    _ => drop(tree.lower_bound_cursor(&key).remove_current()),
+                }
+                let root = tree.root.as_ref().map(|r| r.key);
+                for event in log.take() {
+                    match event {
+                        Inserted { key } => assert!(keys.insert(key)),
+                        Updated { key } => assert!(keys.contains(&key)),
+                        Deleted { key } => assert!(keys.remove(&key)),
+                        Rebuilt {
+                            old_root,
+                            new_root,
+                            size,
+                        } => {
+                            assert!(keys.contains(&old_root) && keys.contains(&new_root));
+                            assert!(size >= 2);
+                        }
+                        RootReplaced { root: replaced } => assert_eq!(replaced, root),
+                    }
+                }
+                assert_valid(&tree);
+                assert!(tree.keys().eq(keys.iter()));
+            }
+            assert!(tree.stats().unwrap().rebuilds > 0);
+        }
+    }
+
+    #[test]
+    fn test_observed_trees_cross_threads() {
+        fn shareable<T: Send + Sync>(_: &T) {}
+
+        let log = RecordingObserver::new();
+        let mut tree = BalancedTree::new();
+        tree.set_observer(log.clone());
+        shareable(&tree);
+        let tree = std::thread::spawn(move || {
+            tree.insert(1, "a");
+            tree
+        })
+        .join()
+        .unwrap();
+        assert_eq!(
+            log.take(),
+            [Inserted { key: 1 }, RootReplaced { root: Some(1) }]
+        );
+        assert_eq!(tree.get(&1), Some(&"a"));
+    }
+}
@@ -0,0 +1,239 @@
+/*
+    Ordered Sets
+        one interface over several balancing strategies
+
+    BalancedTree (size balance with partial rebuilds), AVL, red-black, treap and
+    splay trees all implement `OrderedSet`, so they can be tested against the same
+    conformance suite and compared by the `ordered_sets` benchmark.
+
+    Rotations
+        every strategy except BalancedTree restructures with rotations; the count
+        is kept per tree so the benchmark can report the work done. BalancedTree
+        relinks whole subtrees instead and reports the nodes its rebuilds relinked,
+        which its stats count once enabled
+*/
+
+use crate::BalancedTree;
+
+/// OrderedSet
+///
+/// A set of keys kept in order. `contains` takes `&mut self` because a splay
+/// tree restructures itself on every lookup.
+///
+pub trait OrderedSet<K: Ord> {
+    /// Returns true if `key` was not in the set before.
+    fn insert(&mut self, key: K) -> bool;
+
+    /// Returns true if `key` was in the set.
+    fn delete(&mut self, key: &K) -> bool;
+
+    fn contains(&mut self, key: &K) -> bool;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over the keys in increasing order.
+    fn iter(&self) -> Box<dyn Iterator<Item = &K> + '_>;
+
+    /// Number of nodes on the longest root-to-leaf path.
+    fn height(&self) -> usize;
+
+    /// Total rotations performed since the set was created. A BalancedTree reports
+    /// the nodes relinked by rebuilds since `enable_stats()`, and 0 without stats.
+    fn rotations(&self) -> u64;
+}
+
+/// BinaryNode
+///
+/// Read access to a node of any of the trees, so traversal helpers are shared.
+///
+pub(crate) trait BinaryNode {
+    type Key;
+    fn key(&self) -> &Self::Key;
+    fn left(&self) -> Option<&Self>;
+    fn right(&self) -> Option<&Self>;
+}
+
+/// InOrder
+///
+/// Borrowing in-order iterator over any BinaryNode tree, using an explicit stack
+/// so that degenerate (path-shaped) trees do not overflow the call stack.
+///
+pub(crate) struct InOrder<'a, N> {
+    stack: Vec<&'a N>,
+}
+
+impl<'a, N> InOrder<'a, N> {
+    pub(crate) fn new(root: Option<&'a N>) -> Self
+    where
+        N: BinaryNode,
+    {
+        let mut iter = InOrder { stack: Vec::new() };
+        iter.push_left_spine(root);
+        iter
+    }
+
+    fn push_left_spine(&mut self, mut cur: Option<&'a N>)
+    where
+        N: BinaryNode,
+    {
+        while let Some(node) = cur {
+            self.stack.push(node);
+            cur = node.left();
+        }
+    }
+}
+
+impl<'a, N: BinaryNode> Iterator for InOrder<'a, N> {
+    type Item = &'a N::Key;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(node.right());
+        Some(node.key())
+    }
+}
+
+/// height()
+///
+/// Iterative height of a BinaryNode tree.
+///
+/// Returns the number of nodes on the longest root-to-leaf path.
+///
+pub(crate) fn height<N: BinaryNode>(root: Option<&N>) -> usize {
+    let mut stack: Vec<(&N, usize)> = root.into_iter().map(|n| (n, 1)).collect();
+    let mut tallest = 0;
+    while let Some((node, depth)) = stack.pop() {
+        tallest = tallest.max(depth);
+        stack.extend(
+            node.left()
+                .into_iter()
+                .chain(node.right())
+                .map(|c| (c, depth + 1)),
+        );
+    }
+    tallest
+}
+
+impl<K: Ord> OrderedSet<K> for BalancedTree<K, ()> {
+    fn insert(&mut self, key: K) -> bool {
+        BalancedTree::insert(self, key, ()).is_none()
+    }
+
+    fn delete(&mut self, key: &K) -> bool {
+        self.remove(key).is_some()
+    }
+
+    fn contains(&mut self, key: &K) -> bool {
+        self.contains_key(key)
+    }
+
+    fn len(&self) -> usize {
+        BalancedTree::len(self)
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &K> + '_> {
+        Box::new(self.keys())
+    }
+
+    fn height(&self) -> usize {
+        BalancedTree::height(self)
+    }
+
+    fn rotations(&self) -> u64 {
+        // Rebalancing relinks whole subtrees instead of rotating
+        self.stats().map_or(0, |stats| stats.nodes_rebuilt)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::tests::{assert_valid, xorshift};
+    use std::collections::BTreeSet;
+
+    // The conformance suite every OrderedSet must pass. `check` is the
+    // implementation's own invariant checker and runs after every update.
+    pub(crate) fn conformance<S: OrderedSet<u32>>(make: impl Fn() -> S, check: impl Fn(&S)) {
+        // Empty set
+        let mut set = make();
+        assert!(set.is_empty());
+        assert_eq!(set.height(), 0);
+        assert!(!set.contains(&1));
+        assert!(!set.delete(&1));
+        check(&set);
+
+        // Duplicates are rejected
+        assert!(set.insert(7));
+        check(&set);
+        assert!(!set.insert(7));
+        check(&set);
+        assert_eq!(set.len(), 1);
+        assert!(set.delete(&7));
+        check(&set);
+        assert!(set.is_empty());
+
+        // Ascending inserts, then descending deletes
+        for key in 0..300 {
+            assert!(set.insert(key));
+            check(&set);
+        }
+        assert_eq!(
+            set.iter().copied().collect::<Vec<_>>(),
+            (0..300).collect::<Vec<_>>()
+        );
+        for key in (0..300).rev() {
+            assert!(set.delete(&key));
+            check(&set);
+        }
+        assert!(set.is_empty());
+
+        // Random operations against BTreeSet
+        let mut next = xorshift(0x5eed_0f0b_adca_fe01);
+        let mut set = make();
+        let mut model = BTreeSet::new();
+        for step in 0..3000 {
+            let key = (next() % 500) as u32;
+            match next() % 3 {
+                0 => assert_eq!(set.insert(key), model.insert(key)),
+                1 => assert_eq!(set.delete(&key), model.remove(&key)),
+                _ => assert_eq!(set.contains(&key), model.contains(&key)),
+            }
+            check(&set);
+            assert_eq!(set.len(), model.len());
+            if step % 100 == 0 {
+                assert!(set.iter().eq(model.iter()));
+                // No binary tree is shorter than ⌈log2(n + 1)⌉
+                let log2 = (usize::BITS - model.len().leading_zeros()) as usize;
+                assert!(log2 <= set.height() && set.height() <= model.len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_balanced_tree_conformance() {
+        conformance(BalancedTree::strict, assert_valid);
+        conformance(BalancedTree::new, assert_valid);
+    }
+
+    #[test]
+    fn test_balanced_tree_rotations_count_nodes_rebuilt() {
+        let mut tree = BalancedTree::<u32, ()>::new();
+        OrderedSet::insert(&mut tree, 0);
+        tree.enable_stats();
+        assert_eq!(OrderedSet::rotations(&tree), 0);
+        for key in 1..100 {
+            OrderedSet::insert(&mut tree, key);
+        }
+        let rebuilt = tree.stats().unwrap().nodes_rebuilt;
+        assert!(rebuilt > 0);
+        assert_eq!(OrderedSet::rotations(&tree), rebuilt);
+
+        // Without stats nothing is counted
+        tree.disable_stats();
+        assert_eq!(OrderedSet::rotations(&tree), 0);
+    }
+}
@@ -0,0 +1,676 @@
+/*
+    Persistent Balanced Tree
+        immutable versions that share every subtree an update did not touch
+
+    `insert` and `remove` never modify a node. They copy the nodes on the search
+    path (path copying) and point the copies at the untouched subtrees of the old
+    version, so each update allocates O(log n) nodes and every earlier version
+    stays valid. Nodes are reference counted with `Arc`, so versions are cheap to
+    clone and can be read from other threads while newer versions are built.
+
+    Balance
+        nodes keep subtree sizes like `BalancedTree`, but rebalancing is done by
+        single and double rotations (weight-balanced trees, Δ = 3, Γ = 2) instead
+        of rebuilding subtrees: a rebuild is only cheap when amortized over later
+        updates, and with persistence the same old version can be updated again
+        and again. Every node satisfies w(heavier) ≤ 3 · w(lighter), where
+        w = size + 1, so the height stays O(log n) in the worst case.
+*/
+
+use std::{cmp::Ordering, collections::HashSet, sync::Arc};
+
+type Link<K, V> = Option<Arc<Node<K, V>>>;
+
+// Rebalance when one side outweighs the other by more than DELTA times
+const DELTA: usize = 3;
+// Use a double rotation when the inner grandchild is at least GAMMA times the outer
+const GAMMA: usize = 2;
+
+#[derive(Clone)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    size: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+fn size<K, V>(node: &Link<K, V>) -> usize {
+    node.as_ref().map_or(0, |n| n.size)
+}
+
+fn weight<K, V>(node: &Link<K, V>) -> usize {
+    size(node) + 1
+}
+
+/// Change
+///
+/// One difference between two versions, as reported by `PersistentTree::diff`.
+///
+#[derive(Debug, PartialEq, Eq)]
+pub enum Change<'a, K, V> {
+    Added { key: &'a K, value: &'a V },
+    Removed { key: &'a K, value: &'a V },
+    Changed { key: &'a K, old: &'a V, new: &'a V },
+}
+
+pub struct PersistentTree<K, V> {
+    /*
+        One version of a persistent ordered map. Cloning a version is O(1) and
+        shares the whole tree.
+    */
+    root: Link<K, V>,
+}
+
+impl<K, V> Clone for PersistentTree<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Default for PersistentTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> PersistentTree<K, V> {
+    pub fn new() -> Self {
+        /*
+            Create a new, empty version.
+        */
+        Self { root: None }
+    }
+
+    pub fn len(&self) -> usize {
+        /*
+            Number of keys in this version, read from the root's cached size.
+        */
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        /*
+            Whether this version holds no keys.
+        */
+        self.root.is_none()
+    }
+
+    pub fn height(&self) -> usize {
+        /*
+            Number of nodes on the longest root-to-leaf path; 0 for an empty tree.
+        */
+        fn height<K, V>(node: &Link<K, V>) -> usize {
+            node.as_ref()
+                .map_or(0, |n| 1 + height(&n.left).max(height(&n.right)))
+        }
+        height(&self.root)
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        /*
+            Look up the value stored under `key` in this version.
+        */
+        let mut cur = self.root.as_deref();
+        while let Some(node) = cur {
+            cur = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Greater => node.right.as_deref(),
+                Ordering::Equal => return Some(&node.value),
+            };
+        }
+        None
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        /*
+            Whether `key` is present in this version.
+        */
+        self.get(key).is_some()
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        /*
+            Iterate over `(&key, &value)` in key order.
+        */
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left_spine(self.root.as_deref());
+        iter
+    }
+
+    pub fn insert(&self, key: K, value: V) -> Self {
+        /*
+            Return a new version with `key` mapped to `value`. This version is left
+            unchanged; only the O(log n) nodes on the search path are copied.
+        */
+        Self {
+            root: Some(Self::insert_in(&self.root, key, value)),
+        }
+    }
+
+    pub fn remove(&self, key: &K) -> Self {
+        /*
+            Return a new version without `key`. If `key` is absent the new version
+            shares the whole tree with this one.
+        */
+        match Self::delete_in(&self.root, key) {
+            Some(root) => Self { root },
+            None => self.clone(),
+        }
+    }
+
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        /*
+            Whether both versions are the very same tree, not merely equal.
+        */
+        match (&self.root, &other.root) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    pub fn shared_nodes(&self, other: &Self) -> usize {
+        /*
+            Number of nodes of this version that are physically shared with
+            `other`. A shared node's whole subtree is shared, so each shared subtree
+            is counted from its cached size without being walked.
+        */
+        let mut theirs = HashSet::new();
+        let mut stack: Vec<&Arc<Node<K, V>>> = other.root.iter().collect();
+        while let Some(node) = stack.pop() {
+            theirs.insert(Arc::as_ptr(node));
+            stack.extend(node.left.iter().chain(node.right.iter()));
+        }
+
+        let mut shared = 0;
+        let mut stack: Vec<&Arc<Node<K, V>>> = self.root.iter().collect();
+        while let Some(node) = stack.pop() {
+            if theirs.contains(&Arc::as_ptr(node)) {
+                shared += node.size;
+            } else {
+                stack.extend(node.left.iter().chain(node.right.iter()));
+            }
+        }
+        shared
+    }
+
+    pub fn diff<'a>(&'a self, newer: &'a Self) -> Vec<Change<'a, K, V>>
+    where
+        V: PartialEq,
+    {
+        /*
+            The changes that turn this version into `newer`, in key order.
+
+            Both versions are walked in order at once, each as a stack of pending
+            subtrees and entries. A subtree is only opened up when the other side
+            needs to get past its smallest key, so a subtree shared by both versions
+            reaches the top of both stacks whole and is skipped by pointer. Comparing
+            a version with one derived from it by k updates touches O(k log² n)
+            nodes instead of all of them.
+        */
+        let mut old: Vec<Pending<'a, K, V>> = self.root.iter().map(Pending::Tree).collect();
+        let mut new: Vec<Pending<'a, K, V>> = newer.root.iter().map(Pending::Tree).collect();
+        let mut changes = Vec::new();
+        loop {
+            let (a, b) = match (old.last(), new.last()) {
+                (None, None) => break,
+                (Some(Pending::Entry(n)), None) => {
+                    changes.push(Change::Removed {
+                        key: &n.key,
+                        value: &n.value,
+                    });
+                    old.pop();
+                    continue;
+                }
+                (None, Some(Pending::Entry(n))) => {
+                    changes.push(Change::Added {
+                        key: &n.key,
+                        value: &n.value,
+                    });
+                    new.pop();
+                    continue;
+                }
+                (Some(Pending::Tree(_)), None) => {
+                    Pending::open(&mut old);
+                    continue;
+                }
+                (None, Some(Pending::Tree(_))) => {
+                    Pending::open(&mut new);
+                    continue;
+                }
+                (Some(a), Some(b)) => (*a, *b),
+            };
+            if let (Pending::Tree(x), Pending::Tree(y)) = (a, b)
+                && Arc::ptr_eq(x, y)
+            {
+                old.pop();
+                new.pop();
+                continue;
+            }
+            match (a.first_key().cmp(b.first_key()), a, b) {
+                (Ordering::Less, Pending::Entry(n), _) => {
+                    changes.push(Change::Removed {
+                        key: &n.key,
+                        value: &n.value,
+                    });
+                    old.pop();
+                }
+                (Ordering::Greater, _, Pending::Entry(n)) => {
+                    changes.push(Change::Added {
+                        key: &n.key,
+                        value: &n.value,
+                    });
+                    new.pop();
+                }
+                (Ordering::Less, Pending::Tree(_), _) => Pending::open(&mut old),
+                (Ordering::Greater, _, Pending::Tree(_)) => Pending::open(&mut new),
+                (Ordering::Equal, Pending::Entry(x), Pending::Entry(y)) => {
+                    if x.value != y.value {
+                        changes.push(Change::Changed {
+                            key: &x.key,
+                            old: &x.value,
+                            new: &y.value,
+                        });
+                    }
+                    old.pop();
+                    new.pop();
+                }
+                (Ordering::Equal, Pending::Tree(_), Pending::Entry(_)) => Pending::open(&mut old),
+                (Ordering::Equal, Pending::Entry(_), Pending::Tree(_)) => Pending::open(&mut new),
+                (Ordering::Equal, Pending::Tree(x), Pending::Tree(y)) => {
+                    // The smaller one may be shared inside the larger one
+                    if x.size >= y.size {
+                        Pending::open(&mut old);
+                    }
+                    if y.size >= x.size {
+                        Pending::open(&mut new);
+                    }
+                }
+            }
+        }
+        changes
+    }
+
+    fn node(key: K, value: V, left: Link<K, V>, right: Link<K, V>) -> Arc<Node<K, V>> {
+        /*
+            Allocate a node over two existing subtrees, filling in its size.
+        */
+        let size = 1 + size(&left) + size(&right);
+        Arc::new(Node {
+            key,
+            value,
+            size,
+            left,
+            right,
+        })
+    }
+
+    fn balance(key: K, value: V, left: Link<K, V>, right: Link<K, V>) -> Arc<Node<K, V>> {
+        /*
+            `node`, restoring the weight balance after one side gained or lost a
+            single key. Rotated nodes are moved out of their `Arc` when this update
+            created them and copied when an older version still shares them.
+        */
+        if weight(&right) > DELTA * weight(&left) {
+            let r = Arc::unwrap_or_clone(right.unwrap());
+            if weight(&r.left) < GAMMA * weight(&r.right) {
+                let left = Self::node(key, value, left, r.left);
+                return Self::node(r.key, r.value, Some(left), r.right);
+            }
+            let rl = Arc::unwrap_or_clone(r.left.unwrap());
+            let left = Self::node(key, value, left, rl.left);
+            let right = Self::node(r.key, r.value, rl.right, r.right);
+            return Self::node(rl.key, rl.value, Some(left), Some(right));
+        }
+        if weight(&left) > DELTA * weight(&right) {
+            let l = Arc::unwrap_or_clone(left.unwrap());
+            if weight(&l.right) < GAMMA * weight(&l.left) {
+                let right = Self::node(key, value, l.right, right);
+                return Self::node(l.key, l.value, l.left, Some(right));
+            }
+            let lr = Arc::unwrap_or_clone(l.right.unwrap());
+            let left = Self::node(l.key, l.value, l.left, lr.left);
+            let right = Self::node(key, value, lr.right, right);
+            return Self::node(lr.key, lr.value, Some(left), Some(right));
+        }
+        Self::node(key, value, left, right)
+    }
+
+    fn insert_in(node: &Link<K, V>, key: K, value: V) -> Arc<Node<K, V>> {
+        /*
+            Copy the search path for `key` and return the new subtree root.
+        */
+        let Some(n) = node else {
+            return Self::node(key, value, None, None);
+        };
+        let (k, v) = (n.key.clone(), n.value.clone());
+        match key.cmp(&n.key) {
+            Ordering::Less => {
+                let left = Self::insert_in(&n.left, key, value);
+                Self::balance(k, v, Some(left), n.right.clone())
+            }
+            Ordering::Greater => {
+                let right = Self::insert_in(&n.right, key, value);
+                Self::balance(k, v, n.left.clone(), Some(right))
+            }
+            Ordering::Equal => Self::node(key, value, n.left.clone(), n.right.clone()),
+        }
+    }
+
+    fn remove_min(node: &Arc<Node<K, V>>) -> (Link<K, V>, K, V) {
+        /*
+            Copy the path to the minimum of a subtree and return the subtree
+            without it, along with the removed entry.
+        */
+        let Some(left) = &node.left else {
+            return (node.right.clone(), node.key.clone(), node.value.clone());
+        };
+        let (rest, key, value) = Self::remove_min(left);
+        let (k, v) = (node.key.clone(), node.value.clone());
+        (
+            Some(Self::balance(k, v, rest, node.right.clone())),
+            key,
+            value,
+        )
+    }
+
+    fn delete_in(node: &Link<K, V>, key: &K) -> Option<Link<K, V>> {
+        /*
+            Copy the search path for `key` and return the new subtree, or None if
+            `key` is absent and nothing needs copying.
+        */
+        let n = node.as_ref()?;
+        let (k, v) = (n.key.clone(), n.value.clone());
+        Some(match key.cmp(&n.key) {
+            Ordering::Less => {
+                let left = Self::delete_in(&n.left, key)?;
+                Some(Self::balance(k, v, left, n.right.clone()))
+            }
+            Ordering::Greater => {
+                let right = Self::delete_in(&n.right, key)?;
+                Some(Self::balance(k, v, n.left.clone(), right))
+            }
+            Ordering::Equal => match (&n.left, &n.right) {
+                (None, child) | (child, None) => child.clone(),
+                (Some(_), Some(right)) => {
+                    // Both children exist: the successor takes the node's place
+                    let (rest, succ_key, succ_value) = Self::remove_min(right);
+                    Some(Self::balance(succ_key, succ_value, n.left.clone(), rest))
+                }
+            },
+        })
+    }
+}
+
+// A part of a version that `diff` has not compared yet
+enum Pending<'a, K, V> {
+    Tree(&'a Arc<Node<K, V>>),
+    Entry(&'a Node<K, V>),
+}
+
+impl<K, V> Clone for Pending<'_, K, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V> Copy for Pending<'_, K, V> {}
+
+impl<'a, K, V> Pending<'a, K, V> {
+    fn first_key(self) -> &'a K {
+        match self {
+            Pending::Entry(n) => &n.key,
+            Pending::Tree(mut n) => {
+                while let Some(left) = &n.left {
+                    n = left;
+                }
+                &n.key
+            }
+        }
+    }
+
+    // Replaces the subtree on top of `stack` by its left subtree, root entry and
+    // right subtree, smallest on top.
+    fn open(stack: &mut Vec<Self>) {
+        if let Some(Pending::Tree(n)) = stack.pop() {
+            stack.extend(n.right.iter().map(Pending::Tree));
+            stack.push(Pending::Entry(n));
+            stack.extend(n.left.iter().map(Pending::Tree));
+        }
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    /*
+        Borrowing iterator over `(&key, &value)` of one version, in key order.
+    */
+    stack: Vec<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left_spine(&mut self, mut cur: Option<&'a Node<K, V>>) {
+        while let Some(node) = cur {
+            self.stack.push(node);
+            cur = node.left.as_deref();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(node.right.as_deref());
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<'a, K: Ord + Clone, V: Clone> IntoIterator for &'a PersistentTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::xorshift;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    // Checks ordering, cached sizes and the weight balance of every node.
+    fn check<K: Ord, V>(tree: &PersistentTree<K, V>) {
+        fn walk<K: Ord, V>(node: &Link<K, V>, low: Option<&K>, high: Option<&K>) -> usize {
+            let Some(n) = node else {
+                return 0;
+            };
+            assert!(low.is_none_or(|l| *l < n.key) && high.is_none_or(|h| n.key < *h));
+            let l = walk(&n.left, low, Some(&n.key));
+            let r = walk(&n.right, Some(&n.key), high);
+            assert_eq!(n.size, 1 + l + r);
+            assert!((r + 1) <= DELTA * (l + 1) && (l + 1) <= DELTA * (r + 1));
+            n.size
+        }
+        walk(&tree.root, None, None);
+    }
+
+    #[test]
+    fn test_old_versions_stay_valid() {
+        let empty = PersistentTree::new();
+        let one = empty.insert(1, "one");
+        let two = one.insert(2, "two");
+        let replaced = two.insert(1, "uno");
+        let removed = replaced.remove(&2);
+
+        assert!(empty.is_empty());
+        assert_eq!(one.iter().collect::<Vec<_>>(), [(&1, &"one")]);
+        assert_eq!(two.iter().collect::<Vec<_>>(), [(&1, &"one"), (&2, &"two")]);
+        assert_eq!(replaced.get(&1), Some(&"uno"));
+        assert_eq!(two.get(&1), Some(&"one"));
+        assert_eq!(removed.len(), 1);
+        assert!(!removed.contains_key(&2));
+        assert!(two.contains_key(&2));
+        for version in [&empty, &one, &two, &replaced, &removed] {
+            check(version);
+        }
+    }
+
+    #[test]
+    fn test_every_version_matches_btreemap() {
+        let mut next = xorshift(0x0dd_ba11_c0ff_ee00);
+        let mut versions = vec![(PersistentTree::new(), BTreeMap::new())];
+        for _ in 0..2000 {
+            // Update a random earlier version, not only the latest one
+            let from = (next() % versions.len() as u64) as usize;
+            let (tree, model) = &versions[from];
+            let (mut tree, mut model) = (tree.clone(), model.clone());
+            let key = (next() % 300) as u32;
+            if next().is_multiple_of(3) {
+                tree = tree.remove(&key);
+                model.remove(&key);
+            } else {
+                let value = next();
+                tree = tree.insert(key, value);
+                model.insert(key, value);
+            }
+            versions.push((tree, model));
+        }
+        for (tree, model) in &versions {
+            check(tree);
+            assert_eq!(tree.len(), model.len());
+            assert!(tree.iter().eq(model.iter()));
+        }
+    }
+
+    #[test]
+    fn test_updates_share_untouched_subtrees() {
+        let mut tree = PersistentTree::new();
+        for key in 0..1000 {
+            tree = tree.insert(key, ());
+            check(&tree);
+        }
+        // Weight balance with Δ = 3 keeps the height within about 2 · log2(n)
+        assert!(tree.height() <= 20);
+
+        // Only the search path, plus at most a few rotated nodes, is copied
+        let inserted = tree.insert(5000, ());
+        assert!(inserted.shared_nodes(&tree) >= 1000 - tree.height() - 3);
+        let removed = tree.remove(&500);
+        assert!(removed.shared_nodes(&tree) >= 999 - 2 * tree.height() - 3);
+        let replaced = tree.insert(250, ());
+        assert_eq!(replaced.shared_nodes(&tree), 1000 - count_path(&tree, &250));
+
+        // Removing an absent key copies nothing
+        let same = tree.remove(&5000);
+        assert!(same.ptr_eq(&tree));
+        assert_eq!(same.shared_nodes(&tree), 1000);
+        assert_eq!(PersistentTree::<u32, ()>::new().shared_nodes(&tree), 0);
+    }
+
+    // Number of nodes visited by a search for `key`.
+    fn count_path<K: Ord, V>(tree: &PersistentTree<K, V>, key: &K) -> usize {
+        let mut visited = 0;
+        let mut cur = tree.root.as_deref();
+        while let Some(node) = cur {
+            visited += 1;
+            cur = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Greater => node.right.as_deref(),
+                Ordering::Equal => None,
+            };
+        }
+        visited
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut base = PersistentTree::new();
+        for key in 0..100 {
+            base = base.insert(key, key);
+        }
+        let newer = base
+            .insert(7, 70)
+            .remove(&50)
+            .insert(100, 100)
+            .remove(&1000);
+        assert_eq!(
+            base.diff(&newer),
+            [
+                Change::Changed {
+                    key: &7,
+                    old: &7,
+                    new: &70
+                },
+                Change::Removed {
+                    key: &50,
+                    value: &50
+                },
+                Change::Added {
+                    key: &100,
+                    value: &100
+                },
+            ]
+        );
+        assert_eq!(newer.diff(&base).len(), 3);
+        assert!(base.diff(&base).is_empty());
+        // Equal entries count as unchanged even when not shared
+        let rebuilt = (0..100).fold(PersistentTree::new(), |t, k| t.insert(k, k));
+        assert!(base.diff(&rebuilt).is_empty());
+        assert_eq!(PersistentTree::new().diff(&base).len(), 100);
+    }
+
+    #[test]
+    fn test_diff_matches_btreemap() {
+        let mut next = xorshift(0x1234_5678_9abc_def1);
+        let mut base = PersistentTree::new();
+        for _ in 0..500 {
+            base = base.insert((next() % 1000) as u32, next() % 4);
+        }
+        for _ in 0..20 {
+            let mut newer = base.clone();
+            for _ in 0..next() % 30 {
+                let key = (next() % 1000) as u32;
+                newer = match next() % 2 {
+                    0 => newer.remove(&key),
+                    _ => newer.insert(key, next() % 4),
+                };
+            }
+            let old_map: BTreeMap<_, _> = base.iter().collect();
+            let new_map: BTreeMap<_, _> = newer.iter().collect();
+            let mut expected = Vec::new();
+            let keys: BTreeSet<&u32> = old_map.keys().chain(new_map.keys()).copied().collect();
+            for key in keys {
+                match (old_map.get(key), new_map.get(key)) {
+                    (Some(&value), None) => expected.push(Change::Removed { key, value }),
+                    (None, Some(&value)) => expected.push(Change::Added { key, value }),
+                    (Some(&old), Some(&new)) if old != new => {
+                        expected.push(Change::Changed { key, old, new })
+                    }
+                    _ => {}
+                }
+            }
+            assert_eq!(base.diff(&newer), expected);
+        }
+    }
+
+    #[test]
+    fn test_versions_can_be_read_from_other_threads() {
+        let snapshot = (0..1000).fold(PersistentTree::new(), |t, k| t.insert(k, k));
+        std::thread::scope(|s| {
+            let reader = s.spawn(|| snapshot.iter().map(|(_, v)| v).sum::<i32>());
+            let mut latest = snapshot.clone();
+            for key in 0..1000 {
+                latest = latest.remove(&key);
+            }
+            assert!(latest.is_empty());
+            assert_eq!(reader.join().unwrap(), (0..1000).sum());
+        });
+        assert_eq!(snapshot.len(), 1000);
+    }
+}
@@ -0,0 +1,301 @@
+/*
+    Red-Black Tree
+        left-leaning red-black BST (Sedgewick), a 2-3 tree drawn as a binary tree
+
+    Invariants
+        red links lean left, no node has two red links touching it, and every path
+        from the root to an empty link crosses the same number of black links;
+        together these bound the height by 2 · log2(n + 1)
+
+    Updates
+        insert adds a red leaf and fixes violations on the way up with rotations
+        and color flips; delete pushes a red link down the search path so the key
+        is removed from a 3- or 4-node, then fixes the path on the way back up
+*/
+
+use std::cmp::Ordering;
+
+use crate::ordered_set::{BinaryNode, InOrder, OrderedSet};
+
+type Link<K> = Option<Box<Node<K>>>;
+
+struct Node<K> {
+    key: K,
+    // Color of the link from the parent.
+    red: bool,
+    left: Link<K>,
+    right: Link<K>,
+}
+
+impl<K> BinaryNode for Node<K> {
+    type Key = K;
+
+    fn key(&self) -> &K {
+        &self.key
+    }
+
+    fn left(&self) -> Option<&Self> {
+        self.left.as_deref()
+    }
+
+    fn right(&self) -> Option<&Self> {
+        self.right.as_deref()
+    }
+}
+
+fn is_red<K>(node: &Link<K>) -> bool {
+    node.as_ref().is_some_and(|n| n.red)
+}
+
+fn flip_colors<K>(node: &mut Node<K>) {
+    node.red = !node.red;
+    for child in [&mut node.left, &mut node.right].into_iter().flatten() {
+        child.red = !child.red;
+    }
+}
+
+/// RedBlackTree
+///
+/// An OrderedSet kept balanced by link colors.
+///
+pub struct RedBlackTree<K> {
+    root: Link<K>,
+    len: usize,
+    rotations: u64,
+}
+
+impl<K: Ord> Default for RedBlackTree<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord> RedBlackTree<K> {
+    /// new()
+    ///
+    /// Returns an empty RedBlackTree.
+    ///
+    pub fn new() -> Self {
+        RedBlackTree {
+            root: None,
+            len: 0,
+            rotations: 0,
+        }
+    }
+
+    fn rotate_left(&mut self, mut node: Box<Node<K>>) -> Box<Node<K>> {
+        self.rotations += 1;
+        let mut pivot = node.right.take().expect("rotate_left needs a right child");
+        node.right = pivot.left.take();
+        pivot.red = node.red;
+        node.red = true;
+        pivot.left = Some(node);
+        pivot
+    }
+
+    fn rotate_right(&mut self, mut node: Box<Node<K>>) -> Box<Node<K>> {
+        self.rotations += 1;
+        let mut pivot = node.left.take().expect("rotate_right needs a left child");
+        node.left = pivot.right.take();
+        pivot.red = node.red;
+        node.red = true;
+        pivot.right = Some(node);
+        pivot
+    }
+
+    // Restores the left-leaning invariants at `node` on the way back up.
+    fn balance(&mut self, mut node: Box<Node<K>>) -> Box<Node<K>> {
+        if is_red(&node.right) && !is_red(&node.left) {
+            node = self.rotate_left(node);
+        }
+        if is_red(&node.left) && node.left.as_ref().is_some_and(|l| is_red(&l.left)) {
+            node = self.rotate_right(node);
+        }
+        if is_red(&node.left) && is_red(&node.right) {
+            flip_colors(&mut node);
+        }
+        node
+    }
+
+    // Makes node.left or one of its children red, assuming node is red and both
+    // node.left and node.left.left are black.
+    fn move_red_left(&mut self, mut node: Box<Node<K>>) -> Box<Node<K>> {
+        flip_colors(&mut node);
+        if node.right.as_ref().is_some_and(|r| is_red(&r.left)) {
+            let right = node.right.take().unwrap();
+            node.right = Some(self.rotate_right(right));
+            node = self.rotate_left(node);
+            flip_colors(&mut node);
+        }
+        node
+    }
+
+    // Makes node.right or one of its children red, assuming node is red and both
+    // node.right and node.right.left are black.
+    fn move_red_right(&mut self, mut node: Box<Node<K>>) -> Box<Node<K>> {
+        flip_colors(&mut node);
+        if node.left.as_ref().is_some_and(|l| is_red(&l.left)) {
+            node = self.rotate_right(node);
+            flip_colors(&mut node);
+        }
+        node
+    }
+
+    fn insert_in(&mut self, node: Link<K>, key: K, inserted: &mut bool) -> Box<Node<K>> {
+        let Some(mut n) = node else {
+            *inserted = true;
+            return Box::new(Node {
+                key,
+                red: true,
+                left: None,
+                right: None,
+            });
+        };
+        match key.cmp(&n.key) {
+            Ordering::Less => n.left = Some(self.insert_in(n.left.take(), key, inserted)),
+            Ordering::Greater => n.right = Some(self.insert_in(n.right.take(), key, inserted)),
+            Ordering::Equal => return n,
+        }
+        self.balance(n)
+    }
+
+    // Unlinks the minimum of a subtree, returning the rest and the removed key.
+    fn delete_min(&mut self, mut node: Box<Node<K>>) -> (Link<K>, K) {
+        if node.left.is_none() {
+            return (None, node.key);
+        }
+        if !is_red(&node.left) && !node.left.as_ref().is_some_and(|l| is_red(&l.left)) {
+            node = self.move_red_left(node);
+        }
+        let (rest, min) = self.delete_min(node.left.take().unwrap());
+        node.left = rest;
+        (Some(self.balance(node)), min)
+    }
+
+    // `key` must be present in the subtree.
+    fn delete_in(&mut self, mut node: Box<Node<K>>, key: &K) -> Link<K> {
+        if *key < node.key {
+            if !is_red(&node.left) && !node.left.as_ref().is_some_and(|l| is_red(&l.left)) {
+                node = self.move_red_left(node);
+            }
+            let left = node.left.take().unwrap();
+            node.left = self.delete_in(left, key);
+        } else {
+            if is_red(&node.left) {
+                node = self.rotate_right(node);
+            }
+            if *key == node.key && node.right.is_none() {
+                return None;
+            }
+            if !is_red(&node.right) && !node.right.as_ref().is_some_and(|r| is_red(&r.left)) {
+                node = self.move_red_right(node);
+            }
+            let right = node.right.take().unwrap();
+            if *key == node.key {
+                let (rest, successor) = self.delete_min(right);
+                node.key = successor;
+                node.right = rest;
+            } else {
+                node.right = self.delete_in(right, key);
+            }
+        }
+        Some(self.balance(node))
+    }
+}
+
+impl<K: Ord> OrderedSet<K> for RedBlackTree<K> {
+    fn insert(&mut self, key: K) -> bool {
+        let mut inserted = false;
+        let root = self.root.take();
+        let mut root = self.insert_in(root, key, &mut inserted);
+        root.red = false;
+        self.root = Some(root);
+        self.len += inserted as usize;
+        inserted
+    }
+
+    fn delete(&mut self, key: &K) -> bool {
+        if !self.contains(key) {
+            return false;
+        }
+        let mut root = self.root.take().unwrap();
+        if !is_red(&root.left) && !is_red(&root.right) {
+            root.red = true;
+        }
+        self.root = self.delete_in(root, key);
+        if let Some(root) = self.root.as_mut() {
+            root.red = false;
+        }
+        self.len -= 1;
+        true
+    }
+
+    fn contains(&mut self, key: &K) -> bool {
+        let mut cur = self.root.as_deref();
+        while let Some(node) = cur {
+            cur = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Greater => node.right.as_deref(),
+                Ordering::Equal => return true,
+            };
+        }
+        false
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &K> + '_> {
+        Box::new(InOrder::new(self.root.as_deref()))
+    }
+
+    fn height(&self) -> usize {
+        crate::ordered_set::height(self.root.as_deref())
+    }
+
+    fn rotations(&self) -> u64 {
+        self.rotations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ordered_set::tests::conformance;
+
+    // Checks ordering, the root color, left-leaning red links, no two reds in a
+    // row and equal black height on every path.
+    fn check<K: Ord>(tree: &RedBlackTree<K>) {
+        fn walk<K: Ord>(node: &Link<K>, low: Option<&K>, high: Option<&K>) -> (usize, usize) {
+            let Some(n) = node else {
+                return (0, 0);
+            };
+            assert!(low.is_none_or(|l| *l < n.key) && high.is_none_or(|h| n.key < *h));
+            assert!(!is_red(&n.right), "red link leans right");
+            assert!(!(n.red && is_red(&n.left)), "two red links in a row");
+            let (ls, lb) = walk(&n.left, low, Some(&n.key));
+            let (rs, rb) = walk(&n.right, Some(&n.key), high);
+            assert_eq!(lb, rb, "black heights differ");
+            (1 + ls + rs, lb + !n.red as usize)
+        }
+        assert!(!is_red(&tree.root));
+        assert_eq!(walk(&tree.root, None, None).0, tree.len);
+    }
+
+    #[test]
+    fn test_conformance() {
+        conformance(RedBlackTree::new, check);
+    }
+
+    #[test]
+    fn test_height_bound() {
+        let mut tree = RedBlackTree::new();
+        for key in 0..1000 {
+            tree.insert(key);
+            check(&tree);
+        }
+        // 2 · log2(1001) ≈ 19.9
+        assert!(tree.height() <= 19);
+    }
+}
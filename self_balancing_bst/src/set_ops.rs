@@ -0,0 +1,414 @@
+/*
+    Set Algebra
+        split, join, union, intersection and differences of BalancedTrees
+
+    Everything is built from two primitives on subtrees:
+        split(t, x)      -> (keys < x, the entry at x if any, keys > x)
+        join(l, mid, r)  -> one tree, given every key of l < mid < every key of r
+
+    `join` walks down the spine of the heavier side until the sizes are within α of
+    each other, hangs the lighter side there, and fixes counts on the way back up,
+    rebuilding any node left out of balance exactly as `insert` does. The set
+    operations split one tree by the other's root and recurse on both halves, which
+    touches O(m log(n/m + 1)) nodes for trees of sizes m ≤ n apart from rebuilds.
+
+    Cost by balance mode
+        default trees: a node is rebuilt only once its heavier side holds more
+            than α of it, which the cuts and joins seldom cause, so the set
+            operations stay at O(m log(n/m + 1)) and split/join at O(log n)
+        `strict()` trees: almost every cut or join leaves some node with
+            |left − right| > 1, often near the root, so the rebuilds cost O(n + m):
+            even a union of n keys with 4 keys, or one split, is linear in n
+    `cargo bench --bench balanced_tree` times both modes at growing n.
+
+    Results keep the balance mode of `self` (of `left` for `join`). Subtrees of the
+    other tree are spliced in as they are, so when `self` is `strict()` and that
+    tree is not, it is first rebuilt into the perfectly balanced shape in O(m).
+*/
+
+use crate::{BalancedTree, TreeNode, augment::Augment, observer::Op};
+
+type Link<K, V, A> = Option<Box<TreeNode<K, V, <A as Augment<K, V>>::Summary>>>;
+// Keys below the split key, the node holding it, keys above it
+type Split<K, V, A> = (Link<K, V, A>, Link<K, V, A>, Link<K, V, A>);
+
+impl<K: Ord, V, A: Augment<K, V>> BalancedTree<K, V, A> {
+    fn join_with(
+        alpha: (usize, usize),
+        left: Link<K, V, A>,
+        mut mid: Box<TreeNode<K, V, A::Summary>>,
+        right: Link<K, V, A>,
+    ) -> Link<K, V, A> {
+        /*
+            Join `left`, `mid` and `right` into one subtree. Node `mid` is reused as
+            is; its own children are ignored.
+        */
+        let l = TreeNode::subtree_size(&left);
+        let r = TreeNode::subtree_size(&right);
+        if !Self::too_heavy(alpha, l, r) {
+            mid.left = left;
+            mid.left_count = l;
+            mid.right = right;
+            mid.right_count = r;
+            Self::refresh(&mut mid);
+            return Some(mid);
+        }
+
+        let mut node = if l > r {
+            let mut n = left.unwrap();
+            n.right = Self::join_with(alpha, n.right.take(), mid, right);
+            n.right_count = TreeNode::subtree_size(&n.right);
+            Self::refresh(&mut n);
+            Some(n)
+        } else {
+            let mut n = right.unwrap();
+            n.left = Self::join_with(alpha, left, mid, n.left.take());
+            n.left_count = TreeNode::subtree_size(&n.left);
+            Self::refresh(&mut n);
+            Some(n)
+        };
+        if node.as_ref().is_some_and(|n| Self::is_unbalanced(alpha, n)) {
+            Self::rebuild(&mut node, &mut Op::silent());
+        }
+        node
+    }
+
+    fn adopt(alpha: (usize, usize), other: Self) -> Link<K, V, A> {
+        /*
+            Hand over `other`'s root for splicing into a tree kept to `alpha`,
+            rebuilding it first if `other` only keeps a looser α.
+        */
+        let mut root = other.root;
+        let (num, den) = other.alpha;
+        if num * alpha.1 > alpha.0 * den && root.is_some() {
+            Self::rebuild(&mut root, &mut Op::silent());
+        }
+        root
+    }
+
+    fn join2(
+        alpha: (usize, usize),
+        mut left: Link<K, V, A>,
+        right: Link<K, V, A>,
+    ) -> Link<K, V, A> {
+        /*
+            Join two subtrees with no entry between them, using the largest entry of
+            `left` as the middle node.
+        */
+        match Self::remove_max(alpha, &mut left, true, &mut Op::silent()) {
+            Some((key, value)) => Self::join_with(alpha, left, Self::leaf(key, value), right),
+            None => right,
+        }
+    }
+
+    fn split(alpha: (usize, usize), node: Link<K, V, A>, key: &K) -> Split<K, V, A> {
+        /*
+            Split a subtree into the keys below `key`, the node holding `key` (if
+            any, with its children detached) and the keys above `key`.
+        */
+        let Some(mut n) = node else {
+            return (None, None, None);
+        };
+        let left = n.left.take();
+        let right = n.right.take();
+        if *key < n.key {
+            let (below, found, above) = Self::split(alpha, left, key);
+            (below, found, Self::join_with(alpha, above, n, right))
+        } else if *key > n.key {
+            let (below, found, above) = Self::split(alpha, right, key);
+            (Self::join_with(alpha, left, n, below), found, above)
+        } else {
+            (left, Some(n), right)
+        }
+    }
+
+    fn union_in(alpha: (usize, usize), a: Link<K, V, A>, b: Link<K, V, A>) -> Link<K, V, A> {
+        let Some(mut n) = a else {
+            return b;
+        };
+        if b.is_none() {
+            return Some(n);
+        }
+        let (below, _, above) = Self::split(alpha, b, &n.key);
+        let left = Self::union_in(alpha, n.left.take(), below);
+        let right = Self::union_in(alpha, n.right.take(), above);
+        Self::join_with(alpha, left, n, right)
+    }
+
+    fn intersection_in(alpha: (usize, usize), a: Link<K, V, A>, b: Link<K, V, A>) -> Link<K, V, A> {
+        let (Some(mut n), Some(_)) = (a, b.as_ref()) else {
+            return None;
+        };
+        let (below, found, above) = Self::split(alpha, b, &n.key);
+        let left = Self::intersection_in(alpha, n.left.take(), below);
+        let right = Self::intersection_in(alpha, n.right.take(), above);
+        if found.is_some() {
+            Self::join_with(alpha, left, n, right)
+        } else {
+            Self::join2(alpha, left, right)
+        }
+    }
+
+    fn difference_in(alpha: (usize, usize), a: Link<K, V, A>, b: Link<K, V, A>) -> Link<K, V, A> {
+        let (Some(_), Some(mut m)) = (a.as_ref(), b) else {
+            return a;
+        };
+        let (below, _, above) = Self::split(alpha, a, &m.key);
+        let left = Self::difference_in(alpha, below, m.left.take());
+        let right = Self::difference_in(alpha, above, m.right.take());
+        Self::join2(alpha, left, right)
+    }
+
+    fn symmetric_difference_in(
+        alpha: (usize, usize),
+        a: Link<K, V, A>,
+        b: Link<K, V, A>,
+    ) -> Link<K, V, A> {
+        let Some(mut n) = a else {
+            return b;
+        };
+        if b.is_none() {
+            return Some(n);
+        }
+        let (below, found, above) = Self::split(alpha, b, &n.key);
+        let left = Self::symmetric_difference_in(alpha, n.left.take(), below);
+        let right = Self::symmetric_difference_in(alpha, n.right.take(), above);
+        if found.is_some() {
+            Self::join2(alpha, left, right)
+        } else {
+            Self::join_with(alpha, left, n, right)
+        }
+    }
+
+    pub fn split_at(self, key: &K) -> (Self, Self) {
+        /*
+            Split the tree into the keys below `key` and the keys at or above it.
+            Costs O(height) plus any rebuilds on the two cut paths: O(log n) by
+            default, Θ(n) in `strict()` trees.
+        */
+        let alpha = self.alpha;
+        let (below, found, above) = Self::split(alpha, self.root, key);
+        let above = match found {
+            Some(n) => Self::join_with(alpha, None, n, above),
+            None => above,
+        };
+        (Self::with_root(below, alpha), Self::with_root(above, alpha))
+    }
+
+    pub fn join(left: Self, right: Self) -> Self {
+        /*
+            Concatenate two trees where every key of `left` is below every key of
+            `right`. The result keeps `left`'s balance mode; a relaxed `right` is
+            rebuilt first if `left` is `strict()`.
+
+            Panics if the key ranges overlap.
+        */
+        if let (Some((l, _)), Some((r, _))) = (left.last(), right.first()) {
+            assert!(
+                l < r,
+                "join: every key of left must be below every key of right"
+            );
+        }
+        let alpha = left.alpha;
+        Self::with_root(
+            Self::join2(alpha, left.root, Self::adopt(alpha, right)),
+            alpha,
+        )
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        /*
+            Keys in either tree. Where both trees hold a key, `self`'s value is kept.
+            O(m log(n/m + 1)) for sizes m ≤ n, or O(n + m) if `self` is `strict()`
+            (see the module doc).
+        */
+        let alpha = self.alpha;
+        Self::with_root(
+            Self::union_in(alpha, self.root, Self::adopt(alpha, other)),
+            alpha,
+        )
+    }
+
+    pub fn intersection(self, other: Self) -> Self {
+        /*
+            Keys in both trees, with `self`'s values.
+        */
+        let alpha = self.alpha;
+        Self::with_root(
+            Self::intersection_in(alpha, self.root, Self::adopt(alpha, other)),
+            alpha,
+        )
+    }
+
+    pub fn difference(self, other: Self) -> Self {
+        /*
+            Keys of `self` that are not in `other`.
+        */
+        let alpha = self.alpha;
+        Self::with_root(
+            Self::difference_in(alpha, self.root, Self::adopt(alpha, other)),
+            alpha,
+        )
+    }
+
+    pub fn symmetric_difference(self, other: Self) -> Self {
+        /*
+            Keys in exactly one of the two trees.
+        */
+        let alpha = self.alpha;
+        let root = Self::symmetric_difference_in(alpha, self.root, Self::adopt(alpha, other));
+        Self::with_root(root, alpha)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{assert_valid, xorshift};
+    use std::collections::BTreeSet;
+
+    type SetOp = fn(BalancedTree<u32, ()>, BalancedTree<u32, ()>) -> BalancedTree<u32, ()>;
+
+    fn keys<V>(tree: &BalancedTree<u32, V>) -> Vec<u32> {
+        tree.keys().copied().collect()
+    }
+
+    fn random_set(next: &mut impl FnMut() -> u64, size: u64, range: u64) -> BTreeSet<u32> {
+        (0..size).map(|_| (next() % range) as u32).collect()
+    }
+
+    fn tree_of(set: &BTreeSet<u32>, strict: bool) -> BalancedTree<u32, ()> {
+        let mut tree = if strict {
+            BalancedTree::strict()
+        } else {
+            BalancedTree::new()
+        };
+        for &k in set {
+            tree.insert(k, ());
+            assert_valid(&tree);
+        }
+        tree
+    }
+
+    #[test]
+    fn test_split_and_join() {
+        let tree = BalancedTree::from_sorted((0..100u32).map(|k| (k, k)));
+        assert_valid(&tree);
+        let (low, high) = tree.split_at(&40);
+        assert_valid(&low);
+        assert_valid(&high);
+        assert_eq!(keys(&low), (0..40).collect::<Vec<_>>());
+        assert_eq!(keys(&high), (40..100).collect::<Vec<_>>());
+
+        // Lopsided join: 3 keys against 60
+        let (tiny, rest) = low.split_at(&3);
+        assert_valid(&tiny);
+        assert_valid(&rest);
+        let joined = BalancedTree::join(tiny, high);
+        assert_valid(&joined);
+        assert_eq!(
+            keys(&joined),
+            [0, 1, 2].into_iter().chain(40..100).collect::<Vec<_>>()
+        );
+        assert_eq!(joined.get(&41), Some(&41));
+
+        let (empty, all) = joined.split_at(&0);
+        assert_valid(&empty);
+        assert_valid(&all);
+        assert!(empty.is_empty());
+        assert_eq!(all.len(), 63);
+    }
+
+    #[test]
+    #[should_panic(expected = "join")]
+    fn test_join_rejects_overlap() {
+        let a = BalancedTree::from_sorted([(1, ()), (5, ())]);
+        let b = BalancedTree::from_sorted([(3, ())]);
+        BalancedTree::join(a, b);
+    }
+
+    #[test]
+    fn test_union_keeps_left_values() {
+        let a = BalancedTree::from_sorted([(1, 'a'), (2, 'a')]);
+        let b = BalancedTree::from_sorted([(2, 'b'), (3, 'b')]);
+        let u = a.union(b);
+        assert_valid(&u);
+        let entries: Vec<(i32, char)> = u.iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(entries, vec![(1, 'a'), (2, 'a'), (3, 'b')]);
+    }
+
+    #[test]
+    fn test_set_operations_match_btreeset() {
+        let mut next = xorshift(0x0123_4567_89ab_cdef);
+        for round in 0..60 {
+            let strict = round % 3 == 0;
+            // Mix of similar sizes and very different sizes
+            let size_b = if round % 2 == 0 {
+                next() % 200
+            } else {
+                next() % 8
+            };
+            let size_a = next() % 200;
+            let a = random_set(&mut next, size_a, 300);
+            let b = random_set(&mut next, size_b, 300);
+
+            let checks: [(SetOp, Vec<u32>); 4] = [
+                (BalancedTree::union, a.union(&b).copied().collect()),
+                (
+                    BalancedTree::intersection,
+                    a.intersection(&b).copied().collect(),
+                ),
+                (
+                    BalancedTree::difference,
+                    a.difference(&b).copied().collect(),
+                ),
+                (
+                    BalancedTree::symmetric_difference,
+                    a.symmetric_difference(&b).copied().collect(),
+                ),
+            ];
+            for (op, expected) in checks {
+                let result = op(tree_of(&a, strict), tree_of(&b, strict));
+                assert_valid(&result);
+                assert_eq!(keys(&result), expected);
+            }
+
+            let pivot = (next() % 300) as u32;
+            let (low, high) = tree_of(&a, strict).split_at(&pivot);
+            assert_valid(&low);
+            assert_valid(&high);
+            assert!(low.keys().all(|&k| k < pivot) && high.keys().all(|&k| k >= pivot));
+            let rejoined = BalancedTree::join(low, high);
+            assert_valid(&rejoined);
+            assert_eq!(keys(&rejoined), a.iter().copied().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_mixed_modes_keep_the_stricter_invariant() {
+        // Sorted inserts leave a relaxed tree far from perfectly balanced, and
+        // against an empty tree all of it would be handed back as it is
+        let a = BTreeSet::new();
+        let b: BTreeSet<u32> = (0..300).collect();
+        let ops: [SetOp; 4] = [
+            BalancedTree::union,
+            BalancedTree::intersection,
+            BalancedTree::difference,
+            BalancedTree::symmetric_difference,
+        ];
+        for op in ops {
+            for strict in [true, false] {
+                let result = op(tree_of(&a, strict), tree_of(&b, !strict));
+                assert_eq!(result.is_strict(), strict);
+                assert_valid(&result);
+            }
+        }
+
+        let low = tree_of(&(0..100).collect(), true);
+        let high = tree_of(&(100..300).collect(), false);
+        let joined = BalancedTree::join(low, high);
+        assert!(joined.is_strict());
+        assert_valid(&joined);
+        assert_eq!(keys(&joined), (0..300).collect::<Vec<_>>());
+    }
+}
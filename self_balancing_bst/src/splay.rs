@@ -0,0 +1,270 @@
+/*
+    Splay Tree
+        self-adjusting BST with no balance information at all
+
+    Every access splays the key it looked for (or the last node on its search
+    path) to the root. A single operation can take Θ(n) on a path-shaped tree, but
+    any sequence of m operations costs O(m log n) in total, and recently used keys
+    stay near the root.
+
+    Splaying is done top-down (Sleator–Tarjan): pieces cut off the search path are
+    collected into a left and a right tree and hung back under the new root at the
+    end. It needs no recursion, which matters because sorted inserts build a path
+    of depth n.
+*/
+
+use std::cmp::Ordering;
+
+use crate::ordered_set::{BinaryNode, InOrder, OrderedSet};
+
+type Link<K> = Option<Box<Node<K>>>;
+
+struct Node<K> {
+    key: K,
+    left: Link<K>,
+    right: Link<K>,
+}
+
+impl<K> BinaryNode for Node<K> {
+    type Key = K;
+
+    fn key(&self) -> &K {
+        &self.key
+    }
+
+    fn left(&self) -> Option<&Self> {
+        self.left.as_deref()
+    }
+
+    fn right(&self) -> Option<&Self> {
+        self.right.as_deref()
+    }
+}
+
+/// SplayTree
+///
+/// An OrderedSet that moves every accessed key to the root.
+///
+pub struct SplayTree<K> {
+    root: Link<K>,
+    len: usize,
+    rotations: u64,
+}
+
+impl<K: Ord> Default for SplayTree<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K> Drop for SplayTree<K> {
+    fn drop(&mut self) {
+        // Unlink iteratively: dropping a path of Boxes recursively could overflow
+        let mut stack: Vec<Box<Node<K>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+impl<K: Ord> SplayTree<K> {
+    /// new()
+    ///
+    /// Returns an empty SplayTree.
+    ///
+    pub fn new() -> Self {
+        SplayTree {
+            root: None,
+            len: 0,
+            rotations: 0,
+        }
+    }
+
+    // Splays `key`, or the last node on its search path, to the root. Every
+    // rotation and every link counts as one rotation of bottom-up splaying.
+    fn splay(&mut self, key: &K) {
+        let Some(mut top) = self.root.take() else {
+            return;
+        };
+        // Pieces smaller than `key`, each hung as the right child of the previous one
+        let mut smaller: Vec<Box<Node<K>>> = Vec::new();
+        // Pieces larger than `key`, each hung as the left child of the previous one
+        let mut larger: Vec<Box<Node<K>>> = Vec::new();
+
+        loop {
+            match key.cmp(&top.key) {
+                Ordering::Less => {
+                    let Some(mut child) = top.left.take() else {
+                        break;
+                    };
+                    if *key < child.key {
+                        // Zig-zig: rotate right before linking
+                        top.left = child.right.take();
+                        child.right = Some(top);
+                        top = child;
+                        self.rotations += 1;
+                        let Some(next) = top.left.take() else { break };
+                        child = next;
+                    }
+                    larger.push(top);
+                    top = child;
+                    self.rotations += 1;
+                }
+                Ordering::Greater => {
+                    let Some(mut child) = top.right.take() else {
+                        break;
+                    };
+                    if *key > child.key {
+                        // Zag-zag: rotate left before linking
+                        top.right = child.left.take();
+                        child.left = Some(top);
+                        top = child;
+                        self.rotations += 1;
+                        let Some(next) = top.right.take() else { break };
+                        child = next;
+                    }
+                    smaller.push(top);
+                    top = child;
+                    self.rotations += 1;
+                }
+                Ordering::Equal => break,
+            }
+        }
+
+        let mut left = top.left.take();
+        for mut piece in smaller.into_iter().rev() {
+            piece.right = left;
+            left = Some(piece);
+        }
+        let mut right = top.right.take();
+        for mut piece in larger.into_iter().rev() {
+            piece.left = right;
+            right = Some(piece);
+        }
+        top.left = left;
+        top.right = right;
+        self.root = Some(top);
+    }
+}
+
+impl<K: Ord> OrderedSet<K> for SplayTree<K> {
+    fn insert(&mut self, key: K) -> bool {
+        self.splay(&key);
+        let mut node = Box::new(Node {
+            key,
+            left: None,
+            right: None,
+        });
+        match self.root.take() {
+            None => {}
+            Some(mut root) => match node.key.cmp(&root.key) {
+                Ordering::Equal => {
+                    self.root = Some(root);
+                    return false;
+                }
+                // The new key becomes the root, splitting the old one off
+                Ordering::Less => {
+                    node.left = root.left.take();
+                    node.right = Some(root);
+                }
+                Ordering::Greater => {
+                    node.right = root.right.take();
+                    node.left = Some(root);
+                }
+            },
+        }
+        self.root = Some(node);
+        self.len += 1;
+        true
+    }
+
+    fn delete(&mut self, key: &K) -> bool {
+        if !self.contains(key) {
+            return false;
+        }
+        let mut root = self.root.take().unwrap();
+        self.root = match root.left.take() {
+            None => root.right.take(),
+            Some(left) => {
+                // Splaying the left subtree for `key` brings its maximum to the top
+                let right = root.right.take();
+                self.root = Some(left);
+                self.splay(key);
+                let mut top = self.root.take().unwrap();
+                top.right = right;
+                Some(top)
+            }
+        };
+        self.len -= 1;
+        true
+    }
+
+    fn contains(&mut self, key: &K) -> bool {
+        self.splay(key);
+        self.root.as_ref().is_some_and(|r| r.key == *key)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &K> + '_> {
+        Box::new(InOrder::new(self.root.as_deref()))
+    }
+
+    fn height(&self) -> usize {
+        crate::ordered_set::height(self.root.as_deref())
+    }
+
+    fn rotations(&self) -> u64 {
+        self.rotations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ordered_set::tests::conformance;
+
+    // Checks ordering and the size, iteratively since a splay tree can be a path.
+    fn check<K: Ord>(tree: &SplayTree<K>) {
+        let keys: Vec<&K> = tree.iter().collect();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(keys.len(), tree.len);
+    }
+
+    #[test]
+    fn test_conformance() {
+        conformance(SplayTree::new, check);
+    }
+
+    #[test]
+    fn test_access_moves_key_to_root() {
+        let mut tree = SplayTree::new();
+        for key in 0..100 {
+            tree.insert(key);
+            check(&tree);
+        }
+        // Sorted inserts leave a path
+        assert_eq!(tree.height(), 100);
+        assert!(tree.contains(&0));
+        assert_eq!(tree.root.as_ref().unwrap().key, 0);
+        // Splaying the deepest key roughly halves the depth of the path
+        assert!(tree.height() <= 52);
+        assert!(!tree.contains(&1000));
+        assert_eq!(tree.root.as_ref().unwrap().key, 99);
+    }
+
+    #[test]
+    fn test_deep_path_is_safe() {
+        let mut tree = SplayTree::new();
+        for key in 0..200_000 {
+            tree.insert(key);
+        }
+        check(&tree);
+        assert!(tree.contains(&0));
+        assert!(tree.delete(&100_000));
+        check(&tree);
+    }
+}
@@ -0,0 +1,220 @@
+/*
+    Operation Statistics
+        opt-in counters for the work done by updates
+
+    The tree never rotates: balance is restored by rebuilding the highest node
+    that an update pushed out of balance, at most one per update, and a delete
+    of a node with two children moves its successor up into its place. The
+    counters follow that:
+
+        node_visits      nodes on the search paths of updates
+        rebuilds         subtrees rebuilt into the perfectly balanced shape
+        nodes_rebuilt    nodes relinked by those rebuilds
+        successor_swaps  entries moved up to replace a deleted node
+        height           height of the tree after the latest update
+        max_height       tallest the tree has been after any update
+
+    Updates are insert, remove, pop_first, pop_last and removal through a cursor.
+    The counts for one update are gathered on the stack and dropped unless stats
+    are enabled, so a tree without stats pays nothing. Every node caches the
+    height of its subtree next to its counts, so both heights are read off the
+    root in O(1) after each update. Trees made by the builders, split, join or
+    the set operations start with stats off.
+*/
+
+use crate::{BalancedTree, augment::Augment};
+
+/// TreeStats
+///
+/// Counters accumulated since `enable_stats()`.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TreeStats {
+    pub updates: u64,
+    pub node_visits: u64,
+    pub rebuilds: u64,
+    pub nodes_rebuilt: u64,
+    pub successor_swaps: u64,
+    pub height: usize,
+    pub max_height: usize,
+}
+
+impl TreeStats {
+    /// per_update()
+    ///
+    /// Returns `count` divided by the number of updates, or 0.0 before the first.
+    ///
+    pub fn per_update(&self, count: u64) -> f64 {
+        match self.updates {
+            0 => 0.0,
+            n => count as f64 / n as f64,
+        }
+    }
+}
+
+// The work done by a single update, before it is added to the totals
+#[derive(Default)]
+pub(crate) struct OpCounts {
+    pub(crate) visits: u64,
+    pub(crate) rebuilds: u64,
+    pub(crate) nodes_rebuilt: u64,
+    pub(crate) successor_swaps: u64,
+}
+
+impl<K: Ord, V, A: Augment<K, V>> BalancedTree<K, V, A> {
+    pub fn enable_stats(&mut self) {
+        /*
+            Start counting from zero. Every later update adds its work to the
+            totals; the heights start from the current one.
+        */
+        let stats = TreeStats {
+            height: self.height(),
+            max_height: self.height(),
+            ..TreeStats::default()
+        };
+        self.stats = Some(Box::new(stats));
+    }
+
+    pub fn disable_stats(&mut self) -> Option<TreeStats> {
+        /*
+            Stop counting and hand back the totals, or None if stats were off.
+        */
+        self.stats.take().map(|s| *s)
+    }
+
+    pub fn stats(&self) -> Option<&TreeStats> {
+        /*
+            The totals so far, or None if stats are off.
+        */
+        self.stats.as_deref()
+    }
+
+    pub(crate) fn record(&mut self, op: OpCounts) {
+        /*
+            Add one update's counts to the totals and note the height it left,
+            if stats are enabled.
+        */
+        let height = self.height();
+        let Some(stats) = self.stats.as_mut() else {
+            return;
+        };
+        stats.updates += 1;
+        stats.node_visits += op.visits;
+        stats.rebuilds += op.rebuilds;
+        stats.nodes_rebuilt += op.nodes_rebuilt;
+        stats.successor_swaps += op.successor_swaps;
+        stats.height = height;
+        stats.max_height = stats.max_height.max(height);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{assert_valid, xorshift};
+
+    #[test]
+    fn test_counts_only_when_enabled() {
+        let mut tree = BalancedTree::strict();
+        tree.insert(0, ());
+        assert_eq!(tree.stats(), None);
+
+        tree.enable_stats();
+        assert_eq!(tree.stats().unwrap().height, 1);
+        assert_eq!(tree.stats().unwrap().max_height, 1);
+        for key in 1..64 {
+            tree.insert(key, ());
+        }
+        assert_valid(&tree);
+        let stats = tree.stats().unwrap().clone();
+        assert_eq!(stats.updates, 63);
+        // Strict mode keeps 64 keys at the minimum height of 7
+        assert_eq!(tree.height(), 7);
+        assert_eq!(stats.height, 7);
+        assert_eq!(stats.max_height, 7);
+        assert!(stats.rebuilds > 0);
+        assert!(stats.nodes_rebuilt >= stats.rebuilds);
+        assert_eq!(stats.successor_swaps, 0);
+
+        // The root has two children, so its successor moves up
+        let root = tree.root.as_ref().unwrap().key;
+        tree.remove(&root);
+        assert_eq!(tree.stats().unwrap().successor_swaps, 1);
+        // Missing keys still cost a search
+        let visits = tree.stats().unwrap().node_visits;
+        tree.remove(&1000);
+        assert!(tree.stats().unwrap().node_visits > visits);
+        assert_eq!(tree.stats().unwrap().updates, 65);
+
+        let totals = tree.disable_stats().unwrap();
+        assert_eq!(totals.updates, 65);
+        tree.pop_first();
+        assert_eq!(tree.stats(), None);
+    }
+
+    #[test]
+    fn test_visits_follow_the_height() {
+        for strict in [false, true] {
+            let mut tree = if strict {
+                BalancedTree::strict()
+            } else {
+                BalancedTree::new()
+            };
+            tree.enable_stats();
+            for key in 0..4096u32 {
+                tree.insert(key, ());
+            }
+            let stats = tree.stats().unwrap();
+            assert!(stats.max_height <= if strict { 13 } else { 22 });
+            // Each insert walks one root-to-leaf path
+            assert!(stats.node_visits <= 4096 * stats.max_height as u64);
+            // Sorted input keeps pushing the right spine out of balance, but
+            // no update rebuilds more than once
+            assert!(stats.rebuilds > 0 && stats.rebuilds <= stats.updates);
+
+            let mut cursor = tree.lower_bound_cursor(&0);
+            while cursor.remove_current().is_some() {}
+            drop(cursor);
+            assert_valid(&tree);
+            let stats = tree.stats().unwrap();
+            assert_eq!(stats.updates, 4096 + 4096);
+            assert_eq!(stats.height, 0);
+            assert_eq!(tree.height(), 0);
+        }
+    }
+
+    #[test]
+    fn test_heights_match_a_full_walk() {
+        // Number of nodes on the longest path, ignoring the cached heights
+        fn walk<K, V, S>(node: &Option<Box<crate::TreeNode<K, V, S>>>) -> usize {
+            node.as_ref()
+                .map_or(0, |n| 1 + walk(&n.left).max(walk(&n.right)))
+        }
+
+        let mut next = xorshift(0x7a11_7ee5_0dd5_1d3a);
+        for strict in [true, false] {
+            let mut tree = if strict {
+                BalancedTree::strict()
+            } else {
+                BalancedTree::new()
+            };
+            tree.extend((0..50u32).map(|k| (k * 7, ())));
+            tree.enable_stats();
+            let mut tallest = walk(&tree.root);
+            for _ in 0..3000 {
+                let key = (next() % 700) as u32;
+                match next() % 4 {
+                    0 => drop(tree.remove(&key)),
+                    1 => drop(tree.entry(key).or_insert(())),
+                    2 => drop(tree.lower_bound_cursor(&key).remove_current()),
+                    _ => drop(tree.insert(key, ())),
+                }
+                let height = walk(&tree.root);
+                tallest = tallest.max(height);
+                assert_valid(&tree);
+                assert_eq!(tree.stats().unwrap().height, height);
+                assert_eq!(tree.stats().unwrap().max_height, tallest);
+            }
+        }
+    }
+}
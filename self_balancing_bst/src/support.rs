@@ -0,0 +1,24 @@
+/*
+    Measurement Helpers
+        shared by the benchmarks and the growth measurements
+
+    Public only so that the benches and `src/bin/growth.rs` can reach it; hidden
+    from the docs and not part of the tree's API.
+*/
+
+/// shuffled()
+///
+/// Returns the keys 0..n in a fixed pseudo-random order (Fisher–Yates driven by
+/// xorshift64), so every run sees the same input.
+///
+pub fn shuffled(n: u64) -> Vec<u64> {
+    let mut seed: u64 = 0x853c_49e6_748f_ea9b;
+    let mut keys: Vec<u64> = (0..n).collect();
+    for i in (1..keys.len()).rev() {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        keys.swap(i, (seed % (i as u64 + 1)) as usize);
+    }
+    keys
+}
@@ -0,0 +1,244 @@
+/*
+    Treap
+        BST on keys, max-heap on random priorities
+
+    Each node draws a random priority when it is created. Keeping the tree a heap
+    on those priorities makes its shape that of a BST built by inserting the keys
+    in random order, so the expected height is O(log n) whatever the input order.
+
+    Updates
+        insert adds a leaf and rotates it up while its priority beats its parent's;
+        delete rotates the node down towards its higher-priority child until it is
+        a leaf, then unlinks it
+*/
+
+use std::cmp::Ordering;
+
+use crate::ordered_set::{BinaryNode, InOrder, OrderedSet};
+
+type Link<K> = Option<Box<Node<K>>>;
+
+struct Node<K> {
+    key: K,
+    priority: u64,
+    left: Link<K>,
+    right: Link<K>,
+}
+
+impl<K> BinaryNode for Node<K> {
+    type Key = K;
+
+    fn key(&self) -> &K {
+        &self.key
+    }
+
+    fn left(&self) -> Option<&Self> {
+        self.left.as_deref()
+    }
+
+    fn right(&self) -> Option<&Self> {
+        self.right.as_deref()
+    }
+}
+
+fn priority<K>(node: &Link<K>) -> u64 {
+    node.as_ref().map_or(0, |n| n.priority)
+}
+
+/// Treap
+///
+/// An OrderedSet kept balanced in expectation by random priorities. Priorities
+/// come from an xorshift generator seeded per treap, so runs are reproducible.
+///
+pub struct Treap<K> {
+    root: Link<K>,
+    len: usize,
+    rotations: u64,
+    seed: u64,
+}
+
+impl<K: Ord> Default for Treap<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord> Treap<K> {
+    /// new()
+    ///
+    /// Returns an empty Treap with a fixed seed.
+    ///
+    pub fn new() -> Self {
+        Self::with_seed(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// with_seed()
+    ///
+    /// Returns an empty Treap drawing priorities from `seed` (0 is replaced by 1).
+    ///
+    pub fn with_seed(seed: u64) -> Self {
+        Treap {
+            root: None,
+            len: 0,
+            rotations: 0,
+            seed: seed.max(1),
+        }
+    }
+
+    fn next_priority(&mut self) -> u64 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        self.seed
+    }
+
+    fn rotate_right(&mut self, mut node: Box<Node<K>>) -> Box<Node<K>> {
+        self.rotations += 1;
+        let mut pivot = node.left.take().expect("rotate_right needs a left child");
+        node.left = pivot.right.take();
+        pivot.right = Some(node);
+        pivot
+    }
+
+    fn rotate_left(&mut self, mut node: Box<Node<K>>) -> Box<Node<K>> {
+        self.rotations += 1;
+        let mut pivot = node.right.take().expect("rotate_left needs a right child");
+        node.right = pivot.left.take();
+        pivot.left = Some(node);
+        pivot
+    }
+
+    fn insert_in(&mut self, node: Link<K>, key: K, inserted: &mut bool) -> Box<Node<K>> {
+        let Some(mut n) = node else {
+            *inserted = true;
+            let priority = self.next_priority();
+            return Box::new(Node {
+                key,
+                priority,
+                left: None,
+                right: None,
+            });
+        };
+        match key.cmp(&n.key) {
+            Ordering::Less => {
+                n.left = Some(self.insert_in(n.left.take(), key, inserted));
+                if priority(&n.left) > n.priority {
+                    n = self.rotate_right(n);
+                }
+            }
+            Ordering::Greater => {
+                n.right = Some(self.insert_in(n.right.take(), key, inserted));
+                if priority(&n.right) > n.priority {
+                    n = self.rotate_left(n);
+                }
+            }
+            Ordering::Equal => {}
+        }
+        n
+    }
+
+    fn delete_in(&mut self, node: Link<K>, key: &K, deleted: &mut bool) -> Link<K> {
+        let mut n = node?;
+        match key.cmp(&n.key) {
+            Ordering::Less => n.left = self.delete_in(n.left.take(), key, deleted),
+            Ordering::Greater => n.right = self.delete_in(n.right.take(), key, deleted),
+            Ordering::Equal => {
+                if n.left.is_none() || n.right.is_none() {
+                    *deleted = true;
+                    return n.left.take().or(n.right.take());
+                }
+                // Rotate the higher-priority child up, then keep sinking the key
+                if priority(&n.left) > priority(&n.right) {
+                    n = self.rotate_right(n);
+                    n.right = self.delete_in(n.right.take(), key, deleted);
+                } else {
+                    n = self.rotate_left(n);
+                    n.left = self.delete_in(n.left.take(), key, deleted);
+                }
+            }
+        }
+        Some(n)
+    }
+}
+
+impl<K: Ord> OrderedSet<K> for Treap<K> {
+    fn insert(&mut self, key: K) -> bool {
+        let mut inserted = false;
+        let root = self.root.take();
+        self.root = Some(self.insert_in(root, key, &mut inserted));
+        self.len += inserted as usize;
+        inserted
+    }
+
+    fn delete(&mut self, key: &K) -> bool {
+        let mut deleted = false;
+        let root = self.root.take();
+        self.root = self.delete_in(root, key, &mut deleted);
+        self.len -= deleted as usize;
+        deleted
+    }
+
+    fn contains(&mut self, key: &K) -> bool {
+        let mut cur = self.root.as_deref();
+        while let Some(node) = cur {
+            cur = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Greater => node.right.as_deref(),
+                Ordering::Equal => return true,
+            };
+        }
+        false
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &K> + '_> {
+        Box::new(InOrder::new(self.root.as_deref()))
+    }
+
+    fn height(&self) -> usize {
+        crate::ordered_set::height(self.root.as_deref())
+    }
+
+    fn rotations(&self) -> u64 {
+        self.rotations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ordered_set::tests::conformance;
+
+    // Checks ordering and the heap property on priorities.
+    fn check<K: Ord>(treap: &Treap<K>) {
+        fn walk<K: Ord>(node: &Link<K>, low: Option<&K>, high: Option<&K>) -> usize {
+            let Some(n) = node else {
+                return 0;
+            };
+            assert!(low.is_none_or(|l| *l < n.key) && high.is_none_or(|h| n.key < *h));
+            assert!(priority(&n.left) <= n.priority && priority(&n.right) <= n.priority);
+            1 + walk(&n.left, low, Some(&n.key)) + walk(&n.right, Some(&n.key), high)
+        }
+        assert_eq!(walk(&treap.root, None, None), treap.len);
+    }
+
+    #[test]
+    fn test_conformance() {
+        conformance(Treap::new, check);
+        conformance(|| Treap::with_seed(42), check);
+    }
+
+    #[test]
+    fn test_sorted_input_stays_shallow() {
+        let mut treap = Treap::new();
+        for key in 0..2000 {
+            treap.insert(key);
+            check(&treap);
+        }
+        // Expected height is about 2.99 · ln(n) ≈ 23; a path would be 2000
+        assert!(treap.height() < 60);
+    }
+}
@@ -0,0 +1,347 @@
+/*
+    Invariant Checker
+        every property a BalancedTree relies on, checked from scratch
+
+    Ordering
+        each key is compared against the tightest lower and upper bounds set by its
+        ancestors, not just its parent, so a grandchild on the wrong side is caught
+    Counts
+        `left_count`/`right_count` must equal the real subtree sizes, and
+        `height` the real subtree height
+    Balance
+        `strict()` trees must keep |left − right| ≤ 1 at every node
+        (`Unbalanced`) and a height of at most ⌈log2(n + 1)⌉ (`TooTall`); default
+        trees are held to α = 2/3 instead, where the heavier side of a node may
+        hold at most α of its subtree (`TooHeavy`) and the height is at most
+        `height_bound()` (`TooTallForAlpha`)
+    Summaries
+        each node's `summary` must equal the augmentation recomputed over its
+        subtree
+*/
+
+use crate::{BalancedTree, TreeNode, augment::Augment};
+
+/// TreeViolation
+///
+/// One broken invariant, naming the key of the node where it was found.
+///
+#[derive(Debug, PartialEq, Eq)]
+pub enum TreeViolation<K> {
+    OutOfOrder {
+        key: K,
+        ancestor: K,
+    },
+    StaleCounts {
+        key: K,
+        recorded: (usize, usize),
+        actual: (usize, usize),
+    },
+    StaleHeight {
+        key: K,
+        recorded: usize,
+        actual: usize,
+    },
+    StaleSummary {
+        key: K,
+    },
+    Unbalanced {
+        key: K,
+        left: usize,
+        right: usize,
+    },
+    TooTall {
+        height: usize,
+        bound: usize,
+    },
+    TooHeavy {
+        key: K,
+        left: usize,
+        right: usize,
+    },
+    TooTallForAlpha {
+        height: usize,
+        bound: usize,
+    },
+}
+
+impl<K: Ord, V, A: Augment<K, V>> BalancedTree<K, V, A> {
+    pub fn height_bound(&self) -> usize {
+        /*
+            The tallest a tree of this size may be under its balance mode. A child
+            of a subtree of size s holds at most ⌊α·s⌋ keys, so the bound counts how
+            many times the size can shrink that way before reaching 0. For
+            `strict()` trees this is exactly ⌈log2(n + 1)⌉.
+        */
+        let (num, den) = self.alpha;
+        let mut size = self.len();
+        let mut bound = 0;
+        while size > 0 {
+            bound += 1;
+            size = size * num / den;
+        }
+        bound
+    }
+}
+
+impl<K: Ord + Clone, V, A: Augment<K, V>> BalancedTree<K, V, A> {
+    pub fn validate(&self) -> Result<(), Vec<TreeViolation<K>>> {
+        /*
+            Check every invariant over the whole tree in O(n) and report all the
+            violations found, in pre-order.
+        */
+        // Returns the real size, height and summary of the subtree. Violations
+        // are tagged with the pre-order number of their node, so the ones found
+        // after checking the children can be put back in front of theirs
+        fn check<K: Ord + Clone, V, A: Augment<K, V>>(
+            relaxed: Option<(usize, usize)>,
+            node: &Option<Box<TreeNode<K, V, A::Summary>>>,
+            lower: Option<&K>,
+            upper: Option<&K>,
+            visited: &mut usize,
+            found: &mut Vec<(usize, TreeViolation<K>)>,
+        ) -> (usize, usize, A::Summary) {
+            let Some(n) = node else {
+                return (0, 0, A::empty());
+            };
+            let order = *visited;
+            *visited += 1;
+            let mut here = |violation| found.push((order, violation));
+            let below = lower.filter(|low| n.key <= **low);
+            let above = upper.filter(|high| n.key >= **high);
+            for ancestor in below.into_iter().chain(above) {
+                here(TreeViolation::OutOfOrder {
+                    key: n.key.clone(),
+                    ancestor: ancestor.clone(),
+                });
+            }
+            let (l, left_height, left_summary) =
+                check::<K, V, A>(relaxed, &n.left, lower, Some(&n.key), visited, found);
+            let (r, right_height, right_summary) =
+                check::<K, V, A>(relaxed, &n.right, Some(&n.key), upper, visited, found);
+            let own = A::single(&n.key, &n.value);
+            let summary = A::combine(&A::combine(&left_summary, &own), &right_summary);
+            let height = 1 + left_height.max(right_height);
+
+            let mut here = |violation| found.push((order, violation));
+            if (n.left_count, n.right_count) != (l, r) {
+                here(TreeViolation::StaleCounts {
+                    key: n.key.clone(),
+                    recorded: (n.left_count, n.right_count),
+                    actual: (l, r),
+                });
+            }
+            if n.height != height {
+                here(TreeViolation::StaleHeight {
+                    key: n.key.clone(),
+                    recorded: n.height,
+                    actual: height,
+                });
+            }
+            if n.summary != summary {
+                here(TreeViolation::StaleSummary { key: n.key.clone() });
+            }
+            match relaxed {
+                None if l.abs_diff(r) > 1 => here(TreeViolation::Unbalanced {
+                    key: n.key.clone(),
+                    left: l,
+                    right: r,
+                }),
+                Some(alpha) if BalancedTree::<K, V, A>::too_heavy(alpha, l, r) => {
+                    here(TreeViolation::TooHeavy {
+                        key: n.key.clone(),
+                        left: l,
+                        right: r,
+                    })
+                }
+                _ => {}
+            }
+            (1 + l + r, height, summary)
+        }
+
+        let relaxed = (!self.is_strict()).then_some(self.alpha);
+        let mut found = Vec::new();
+        let (_, height, _) = check::<K, V, A>(relaxed, &self.root, None, None, &mut 0, &mut found);
+        // Stable, so each node's violations keep the order they were found in
+        found.sort_by_key(|&(order, _)| order);
+        let mut violations: Vec<_> = found.into_iter().map(|(_, v)| v).collect();
+        if relaxed.is_some() {
+            let bound = self.height_bound();
+            if height > bound {
+                violations.push(TreeViolation::TooTallForAlpha { height, bound });
+            }
+        } else {
+            // ⌈log2(n + 1)⌉ is the bit length of n
+            let bound = (usize::BITS - self.len().leading_zeros()) as usize;
+            if height > bound {
+                violations.push(TreeViolation::TooTall { height, bound });
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_height_bound() {
+        for n in 0..200 {
+            let tree = BalancedTree::from_sorted((0..n).map(|k| (k, ())));
+            let mut strict = BalancedTree::strict();
+            strict.extend((0..n).map(|k| (k, ())));
+            // ⌈log2(n + 1)⌉ is the bit length of n
+            let log2 = (usize::BITS - (n as usize).leading_zeros()) as usize;
+            assert_eq!(strict.height_bound(), log2);
+            assert!(tree.height_bound() >= log2);
+            assert_eq!(tree.validate(), Ok(()));
+            assert_eq!(strict.validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_reports_deep_order_violation() {
+        let mut tree = BalancedTree::from_sorted((1..=7).map(|k| (k, ())));
+        assert_eq!(tree.validate(), Ok(()));
+
+        // 4 is the root, 2 its left child and 3 the right grandchild: 3 → 5 is
+        // still above its parent 2, so only the bound from the root catches it
+        let two = tree.root.as_mut().unwrap().left.as_mut().unwrap();
+        two.right.as_mut().unwrap().key = 5;
+        assert_eq!(
+            tree.validate(),
+            Err(vec![TreeViolation::OutOfOrder {
+                key: 5,
+                ancestor: 4
+            }])
+        );
+    }
+
+    #[test]
+    fn test_reports_counts_and_balance() {
+        let mut tree = BalancedTree::strict();
+        tree.extend((1..=3).map(|k| (k, ())));
+        assert_eq!(tree.validate(), Ok(()));
+
+        // Hang a fourth key under 3 without fixing any counts or heights
+        let root = tree.root.as_mut().unwrap();
+        let three = root.right.as_mut().unwrap();
+        three.right = Some(Box::new(TreeNode::new(4, (), ())));
+        assert_eq!(
+            tree.validate(),
+            Err(vec![
+                TreeViolation::StaleCounts {
+                    key: 2,
+                    recorded: (1, 1),
+                    actual: (1, 2)
+                },
+                TreeViolation::StaleHeight {
+                    key: 2,
+                    recorded: 2,
+                    actual: 3
+                },
+                TreeViolation::StaleCounts {
+                    key: 3,
+                    recorded: (0, 0),
+                    actual: (0, 1)
+                },
+                TreeViolation::StaleHeight {
+                    key: 3,
+                    recorded: 1,
+                    actual: 2
+                },
+                // The height bound is taken from the (stale) size at the root
+                TreeViolation::TooTall {
+                    height: 3,
+                    bound: 2
+                },
+            ])
+        );
+
+        // Now make the right side two deeper and recount: 2 is out of balance
+        let root = tree.root.as_mut().unwrap();
+        let four = root.right.as_mut().unwrap().right.as_mut().unwrap();
+        four.right = Some(Box::new(TreeNode::new(5, (), ())));
+        four.right_count = 1;
+        four.height = 2;
+        let three = root.right.as_mut().unwrap();
+        three.right_count = 2;
+        three.height = 3;
+        root.right_count = 3;
+        root.height = 4;
+        assert_eq!(
+            tree.validate(),
+            Err(vec![
+                TreeViolation::Unbalanced {
+                    key: 2,
+                    left: 1,
+                    right: 3
+                },
+                TreeViolation::Unbalanced {
+                    key: 3,
+                    left: 0,
+                    right: 2
+                },
+                TreeViolation::TooTall {
+                    height: 4,
+                    bound: 3
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_relaxed_trees_are_held_to_alpha() {
+        // A left spine over keys 1..=n, with correct counts and heights
+        fn spine(n: u32) -> Option<Box<TreeNode<u32, (), ()>>> {
+            (1..=n).fold(None, |below, key| {
+                let mut node = TreeNode::new(key, (), ());
+                node.left_count = key as usize - 1;
+                node.height = key as usize;
+                node.left = below;
+                Some(Box::new(node))
+            })
+        }
+
+        // 3 → 2 → 1 has |left - right| = 2 at 3, which α = 2/3 allows
+        let relaxed = BalancedTree::<u32, ()>::with_root(spine(3), (2, 3));
+        assert_eq!(relaxed.validate(), Ok(()));
+        let strict = BalancedTree::<u32, ()>::with_root(spine(3), (1, 2));
+        assert_eq!(
+            strict.validate(),
+            Err(vec![
+                TreeViolation::Unbalanced {
+                    key: 3,
+                    left: 2,
+                    right: 0
+                },
+                TreeViolation::TooTall {
+                    height: 3,
+                    bound: 2
+                },
+            ])
+        );
+
+        // One more key puts 3 of 4 on one side of the root
+        let relaxed = BalancedTree::<u32, ()>::with_root(spine(4), (2, 3));
+        assert_eq!(
+            relaxed.validate(),
+            Err(vec![
+                TreeViolation::TooHeavy {
+                    key: 4,
+                    left: 3,
+                    right: 0
+                },
+                TreeViolation::TooTallForAlpha {
+                    height: 4,
+                    bound: 3
+                },
+            ])
+        );
+    }
+}